@@ -0,0 +1,124 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+
+/// A builder for starting an application with plugins.
+#[derive(Default)]
+pub struct Builder {
+  plugins: Vec<Box<dyn Plugin>>,
+}
+
+impl Builder {
+  /// Creates a new builder.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds a plugin to set up when the application starts.
+  pub fn add_plugin(mut self, plugin: impl Plugin + 'static) -> Self {
+    self.plugins.push(Box::new(plugin));
+    self
+  }
+
+  /// Starts a new application with the given settings and sets up its
+  /// plugins.
+  ///
+  /// Unless the application is headless, this function opens the application
+  /// window and starts the renderer.
+  pub fn start(
+    self,
+    thread_scope: &thread::Scope,
+    logger: log::Logger,
+    vfs: vfs::Context,
+    mut settings: crate::settings::Context,
+  ) -> Result<Context, StartError> {
+    // Check the plugins before anything is started.
+    let mut plugins = plugin::sort(self.plugins)?;
+
+    settings.register::<Settings>();
+    settings.register::<window::Settings>();
+
+    let app_settings = settings.get::<Settings>()?;
+
+    if let Ok(text) = settings.to_toml() {
+      crash::set_info("Settings", text);
+    }
+
+    let mut ecs = ecs::Context::new();
+
+    let (gfx, window, renderer) = if app_settings.headless {
+      log::info!(logger, "starting in headless mode");
+
+      (None, None, None)
+    } else {
+      let gfx = Arc::new(gfx::Context::new(logger.clone())?);
+      let adapter = gfx.adapter_info();
+
+      crash::set_info(
+        "Graphics adapter",
+        format!(
+          "name: {}\nvendor: {:#06x}\ndevice: {:#06x}\ntype: {:?}",
+          adapter.name, adapter.vendor, adapter.device, adapter.device_type
+        ),
+      );
+
+      let window = window::open(thread_scope, settings.get()?)?;
+      let renderer = gfx::renderer::start(thread_scope, gfx.clone(), &window, logger.clone())?;
+
+      (Some(gfx), Some(window), Some(renderer))
+    };
+
+    // Add a clock resource that ticks once per frame. If an update interval
+    // is set, synchronize frames to it. Headless applications have nothing
+    // else to wait for between frames, so they use a default interval instead
+    // of spinning.
+    let mut clock = time::Clock::new();
+
+    let update_interval = match app_settings.update_interval {
+      None if app_settings.headless => Some(time::hz(Settings::DEFAULT_HEADLESS_UPDATE_RATE)),
+      interval => interval,
+    };
+
+    if let Some(update_interval) = update_interval {
+      clock.set_interval(update_interval);
+      clock.set_intervals_synchronized(true);
+    }
+
+    ecs.put_resource(clock);
+
+    // Add a frame stats resource that records the real time of each frame.
+    let mut frame_stats = time::FrameStats::new();
+
+    frame_stats.set_logger(logger.clone());
+    ecs.put_resource(frame_stats);
+
+    time::set_up_timers(&mut ecs);
+
+    // Publish window events to systems.
+    ecs.add_event_channel::<window::Event>();
+
+    let mut ctx = Context {
+      ecs,
+      gfx,
+      logger,
+      settings,
+      vfs,
+      window,
+      renderer,
+      plugins: Vec::new(),
+      stop_requested: false,
+    };
+
+    for plugin in &mut plugins {
+      log::debug!(ctx.logger, "setting up plugin"; "name" => plugin.name());
+
+      plugin.set_up(&mut ctx);
+    }
+
+    ctx.plugins = plugins;
+
+    Ok(ctx)
+  }
+}
@@ -5,8 +5,129 @@
 use super::*;
 
 pub struct Context {
-  pub ecs: Arc<RwLock<ecs::Context>>,
-  pub gfx: Arc<gfx::Context>,
+  pub ecs: ecs::Context,
+  /// The graphics context, or `None` if the application is headless.
+  pub gfx: Option<Arc<gfx::Context>>,
   pub logger: log::Logger,
-  pub window: window::Handle,
+  pub settings: crate::settings::Context,
+  pub vfs: vfs::Context,
+  /// The application window, or `None` if the application is headless.
+  pub window: Option<window::Handle>,
+  pub(super) renderer: Option<gfx::renderer::Handle>,
+  pub(super) plugins: Vec<Box<dyn Plugin>>,
+  pub(super) stop_requested: bool,
+}
+
+impl Context {
+  /// Runs the main loop of the application until the window is closed or
+  /// `stop()` is called.
+  ///
+  /// Each frame, this function handles window events, which are also written
+  /// to the `ecs::events::EventChannel<window::Event>` resource, ticks the
+  /// `time::Clock` resource and records the frame in the `time::FrameStats`
+  /// resource, and then runs the ECS phases. The `BeforeUpdate` and
+  /// `AfterUpdate` phases run once per frame. The given `state` is updated and
+  /// the `Update` phase runs once per fixed step of the clock in between, or
+  /// once per frame if there is no update interval.
+  ///
+  /// Headless applications have no window events, so frames are driven only by
+  /// the clock, which has an update interval by default so that the loop does
+  /// not spin.
+  pub fn run(&mut self, mut state: impl State) {
+    log::info!(self.logger, "main loop started");
+
+    self.stop_requested = false;
+
+    loop {
+      while let Some(event) = self.window.as_mut().and_then(window::Handle::next_event) {
+        self.ecs.write_event(event.clone());
+
+        state.on_event(self, &event);
+
+        if let window::Event::CloseRequested = event {
+          self.stop_requested = true;
+        }
+      }
+
+      if self.stop_requested {
+        log::info!(self.logger, "main loop stopped");
+
+        return;
+      }
+
+      let updates = {
+        let mut clock = self.ecs.resource_mut::<time::Clock>();
+
+        clock.tick();
+
+        self.ecs.resource_mut::<time::FrameStats>().record(clock.unscaled_elapsed_delta());
+
+        clock.fixed_steps()
+      };
+
+      self.ecs.run_phase(ecs::Phase::BeforeUpdate);
+      self.ecs.commit_entities();
+
+      for _ in 0..updates {
+        state.update(self);
+
+        self.ecs.run_phase(ecs::Phase::Update);
+        self.ecs.commit_entities();
+      }
+
+      self.ecs.run_phase(ecs::Phase::AfterUpdate);
+      self.ecs.commit_entities();
+    }
+  }
+
+  /// Stops the main loop started by `run()` at the beginning of the next
+  /// frame.
+  pub fn stop(&mut self) {
+    self.stop_requested = true;
+  }
+
+  /// Shuts down the application.
+  ///
+  /// Plugins are torn down, the renderer is stopped after the device is idle,
+  /// the window is closed, and then the graphics context is destroyed.
+  /// Finally, the logging module is shut down so that all log records are
+  /// output.
+  pub fn shut_down(mut self) {
+    self.stop_subsystems();
+
+    log::info!(self.logger, "application shut down");
+
+    drop(self);
+
+    log::shut_down();
+  }
+
+  /// Tears down plugins and stops the renderer and window in order. Each step
+  /// only happens once.
+  fn stop_subsystems(&mut self) {
+    // Tear down plugins in the reverse of the order they were set up in.
+    let mut plugins = mem::take(&mut self.plugins);
+
+    for plugin in plugins.iter_mut().rev() {
+      log::debug!(self.logger, "tearing down plugin"; "name" => plugin.name());
+
+      plugin.tear_down(self);
+    }
+
+    if let Some(renderer) = self.renderer.take() {
+      renderer.stop();
+    }
+
+    if let Some(window) = self.window.take() {
+      window.close();
+    }
+
+    self.gfx = None;
+  }
+}
+
+impl Drop for Context {
+  fn drop(&mut self) {
+    self.stop_subsystems();
+  }
 }
@@ -2,42 +2,49 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+mod builder;
 mod context;
+mod plugin;
 mod settings;
+mod state;
 
-pub use self::{context::*, settings::*};
+pub use self::{builder::*, context::*, plugin::*, settings::*, state::*};
 
 use super::*;
 
+/// Starts a new application with the given settings and no plugins.
+///
+/// Unless the application is headless, this function opens the application
+/// window and starts the renderer.
 pub fn start(
   thread_scope: &thread::Scope,
   logger: log::Logger,
-  settings: Settings,
+  vfs: vfs::Context,
+  settings: crate::settings::Context,
 ) -> Result<Context, StartError> {
-  let ecs = Arc::new(RwLock::new(ecs::Context::new()));
-  let gfx = Arc::new(gfx::Context::new(logger.clone())?);
-
-  let window = window::open(thread_scope, settings.window)?;
-
-  gfx::renderer::start(&thread_scope, gfx.clone(), &window, logger.clone())?;
-
-  Ok(Context { ecs, gfx, logger, window })
+  Builder::new().start(thread_scope, logger, vfs, settings)
 }
 
 /// An error that occurred while creating a new application context.
 #[derive(Debug)]
 pub enum StartError {
+  /// The settings contained an invalid value.
+  InvalidSettings(crate::settings::DeserializeError),
   /// An error occurred during initialization of the graphics context.
   GraphicsInitFailed(gfx::InitError),
   /// An error occurred while opening the application window.
   OpenWindowFailed(window::OpenError),
   /// An error occurred while starting the renderer.
   RendererStartFailed(gfx::renderer::StartError),
+  /// The plugins could not be set up.
+  InvalidPlugins(PluginError),
 }
 
 impl fmt::Display for StartError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
+      StartError::InvalidSettings(cause) => write!(f, "invalid settings: {}", cause),
+
       StartError::GraphicsInitFailed(cause) => {
         write!(f, "failed to initialize graphics: {}", cause)
       }
@@ -47,10 +54,19 @@ impl fmt::Display for StartError {
       }
 
       StartError::RendererStartFailed(cause) => write!(f, "failed to start renderer: {}", cause),
+
+      StartError::InvalidPlugins(cause) => write!(f, "invalid plugins: {}", cause),
     }
   }
 }
 
+// Implement `From` to convert from settings deserialization errors.
+impl From<crate::settings::DeserializeError> for StartError {
+  fn from(cause: crate::settings::DeserializeError) -> Self {
+    StartError::InvalidSettings(cause)
+  }
+}
+
 // Implement `From` to convert from out of gfx init errors.
 impl From<gfx::InitError> for StartError {
   fn from(cause: gfx::InitError) -> Self {
@@ -71,3 +87,10 @@ impl From<gfx::renderer::StartError> for StartError {
     StartError::RendererStartFailed(cause)
   }
 }
+
+// Implement `From` to convert from plugin errors.
+impl From<PluginError> for StartError {
+  fn from(cause: PluginError) -> Self {
+    StartError::InvalidPlugins(cause)
+  }
+}
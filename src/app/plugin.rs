@@ -0,0 +1,191 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use std::collections::HashMap;
+
+/// A subsystem that is set up when the application starts and torn down when
+/// it stops.
+///
+/// Plugins are set up after all of the plugins they depend on and torn down
+/// in the reverse order.
+pub trait Plugin {
+  /// Returns the unique name of the plugin.
+  fn name(&self) -> &'static str;
+
+  /// Returns the names of the plugins that must be set up before this one.
+  fn dependencies(&self) -> &[&'static str] {
+    &[]
+  }
+
+  /// Sets up the plugin, such as by adding resources and scheduling systems.
+  fn set_up(&mut self, _ctx: &mut Context) {}
+
+  /// Tears down the plugin when the application stops.
+  fn tear_down(&mut self, _ctx: &mut Context) {}
+}
+
+/// Sorts plugins so that each plugin comes after all of its dependencies.
+///
+/// Plugins are taken in the order they were added in, each preceded by any of
+/// its dependencies that are not already sorted.
+pub(super) fn sort(plugins: Vec<Box<dyn Plugin>>) -> Result<Vec<Box<dyn Plugin>>, PluginError> {
+  let mut indices = HashMap::with_capacity(plugins.len());
+
+  for (index, plugin) in plugins.iter().enumerate() {
+    if indices.insert(plugin.name(), index).is_some() {
+      return Err(PluginError::Duplicate(plugin.name()));
+    }
+  }
+
+  for plugin in &plugins {
+    for dependency in plugin.dependencies() {
+      if !indices.contains_key(dependency) {
+        return Err(PluginError::Missing { plugin: plugin.name(), dependency });
+      }
+    }
+  }
+
+  // Visit each plugin's dependencies before the plugin itself, tracking the
+  // current path to detect cycles.
+  let mut order = Vec::with_capacity(plugins.len());
+  let mut visited = vec![false; plugins.len()];
+  let mut path = Vec::new();
+
+  for index in 0..plugins.len() {
+    visit(&plugins, &indices, index, &mut visited, &mut path, &mut order)?;
+  }
+
+  let mut plugins = plugins.into_iter().map(Some).collect::<Vec<_>>();
+
+  Ok(order.into_iter().filter_map(|index| plugins[index].take()).collect())
+}
+
+/// Adds the plugin at `index` to `order` after its dependencies.
+fn visit(
+  plugins: &[Box<dyn Plugin>],
+  indices: &HashMap<&'static str, usize>,
+  index: usize,
+  visited: &mut [bool],
+  path: &mut Vec<usize>,
+  order: &mut Vec<usize>,
+) -> Result<(), PluginError> {
+  if visited[index] {
+    return Ok(());
+  }
+
+  if let Some(start) = path.iter().position(|i| *i == index) {
+    let cycle = path[start..].iter().map(|i| plugins[*i].name()).collect();
+
+    return Err(PluginError::Cycle(cycle));
+  }
+
+  path.push(index);
+
+  for dependency in plugins[index].dependencies() {
+    visit(plugins, indices, indices[dependency], visited, path, order)?;
+  }
+
+  path.pop();
+
+  visited[index] = true;
+  order.push(index);
+
+  Ok(())
+}
+
+/// An error in the plugins added to an application.
+#[derive(Debug)]
+pub enum PluginError {
+  /// More than one plugin has the same name.
+  Duplicate(&'static str),
+  /// A plugin depends on a plugin that was not added.
+  Missing { plugin: &'static str, dependency: &'static str },
+  /// The plugins depend on each other in a cycle.
+  Cycle(Vec<&'static str>),
+}
+
+impl std::error::Error for PluginError {}
+
+impl fmt::Display for PluginError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PluginError::Duplicate(name) => write!(f, "plugin {:?} was added more than once", name),
+
+      PluginError::Missing { plugin, dependency } => {
+        write!(f, "plugin {:?} depends on plugin {:?}, which was not added", plugin, dependency)
+      }
+
+      PluginError::Cycle(names) => {
+        write!(f, "plugins depend on each other: {}", names.join(" -> "))
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  struct TestPlugin(&'static str, &'static [&'static str]);
+
+  impl Plugin for TestPlugin {
+    fn name(&self) -> &'static str {
+      self.0
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+      self.1
+    }
+  }
+
+  fn sort_names(
+    plugins: &[(&'static str, &'static [&'static str])],
+  ) -> Result<Vec<&'static str>, PluginError> {
+    let plugins = plugins
+      .iter()
+      .map(|(name, dependencies)| Box::new(TestPlugin(name, dependencies)) as Box<dyn Plugin>)
+      .collect();
+
+    Ok(sort(plugins)?.iter().map(|plugin| plugin.name()).collect())
+  }
+
+  #[test]
+  fn plugins_come_after_their_dependencies() {
+    let sorted = sort_names(&[
+      ("game", &["gfx", "audio"]),
+      ("audio", &[]),
+      ("gfx", &["window"]),
+      ("window", &[]),
+    ]);
+
+    assert_eq!(sorted.unwrap(), ["window", "gfx", "audio", "game"]);
+  }
+
+  #[test]
+  fn duplicate_plugins_are_errors() {
+    let result = sort_names(&[("audio", &[]), ("gfx", &[]), ("audio", &[])]);
+
+    assert!(matches!(result, Err(PluginError::Duplicate("audio"))));
+  }
+
+  #[test]
+  fn missing_dependencies_are_errors() {
+    let result = sort_names(&[("game", &["audio"]), ("gfx", &[])]);
+
+    assert!(matches!(result, Err(PluginError::Missing { plugin: "game", dependency: "audio" })));
+  }
+
+  #[test]
+  fn dependency_cycles_are_errors() {
+    let result = sort_names(&[("game", &["gfx"]), ("gfx", &["window"]), ("window", &["gfx"])]);
+
+    match result {
+      Err(PluginError::Cycle(names)) => assert_eq!(names, ["gfx", "window"]),
+      _ => panic!("expected a cycle error"),
+    }
+
+    assert!(matches!(sort_names(&[("game", &["game"])]), Err(PluginError::Cycle(_))));
+  }
+}
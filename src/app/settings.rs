@@ -5,18 +5,28 @@
 use super::*;
 use serde_derive::*;
 
-/// An error that occurred while parsing a TOML config file.
-pub type TomlError = toml::de::Error;
-
+/// Settings for the application, stored in the `[app]` section.
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Settings {
+  /// Interval at which to update the application, such as `"60hz"` or
+  /// `"10ms"`, or `None` to update once per frame.
+  ///
+  /// Headless applications update `DEFAULT_HEADLESS_UPDATE_RATE` times per
+  /// second if this is `None`.
+  #[serde(default)]
+  pub update_interval: Option<time::Duration>,
+  /// Whether to run without a window or graphics context, such as for
+  /// dedicated servers or tests.
   #[serde(default)]
-  pub window: window::Settings,
+  pub headless: bool,
 }
 
 impl Settings {
-  /// Parses configuration options from a string containing TOML.
-  pub fn from_toml(source: &str) -> Result<Self, TomlError> {
-    toml::from_str(source)
-  }
+  /// The number of updates per second of headless applications that do not
+  /// set an update interval.
+  pub const DEFAULT_HEADLESS_UPDATE_RATE: f64 = 60.0;
+}
+
+impl crate::settings::Section for Settings {
+  const NAME: &'static str = "app";
 }
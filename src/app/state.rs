@@ -0,0 +1,27 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+
+/// User-defined application state driven by `Context::run()`.
+///
+/// This trait is implemented for all `FnMut(&mut Context)` closures, which are
+/// called once per update.
+pub trait State {
+  /// Called for each window event before it is otherwise handled.
+  fn on_event(&mut self, _ctx: &mut Context, _event: &window::Event) {}
+
+  /// Called once per update, before the `Update` phase of the ECS runs.
+  ///
+  /// If the application has an update interval, this function may be called
+  /// zero or more times per frame so that it is called once per elapsed
+  /// interval.
+  fn update(&mut self, _ctx: &mut Context) {}
+}
+
+impl<F: FnMut(&mut Context)> State for F {
+  fn update(&mut self, ctx: &mut Context) {
+    self(ctx)
+  }
+}
@@ -0,0 +1,143 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The `crash` module provides an opt-in panic handler that writes crash
+//! reports to the virtual file system.
+
+use super::*;
+use std::backtrace::Backtrace;
+use std::collections::BTreeMap;
+use std::io::Write as _;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{env, panic, thread};
+
+lazy_static! {
+  static ref HANDLER: Mutex<Option<Arc<Handler>>> = Mutex::new(None);
+  static ref INFO: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+}
+
+/// The number of crash reports written, used to give each report a unique
+/// file name.
+static REPORT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Where crash reports are written.
+struct Handler {
+  vfs: vfs::Context,
+  dir: vfs::PathBuf,
+}
+
+/// Installs a panic hook that handles panics on any thread.
+///
+/// When a panic occurs, the panic message and a backtrace are logged at the
+/// `crit` level and a timestamped crash report is written to a new file in
+/// the given directory of the virtual file system. Log records are then
+/// flushed so that they are output even if the panic ends the program. The
+/// logging module keeps running in case the panic is caught.
+///
+/// The previously installed panic hook is called afterwards.
+pub fn install(vfs: vfs::Context, dir: impl Into<vfs::PathBuf>) {
+  *HANDLER.lock() = Some(Arc::new(Handler { vfs, dir: dir.into() }));
+
+  let previous_hook = panic::take_hook();
+
+  panic::set_hook(Box::new(move |info| {
+    let message = match info.payload().downcast_ref::<&str>() {
+      Some(message) => (*message).to_owned(),
+
+      None => match info.payload().downcast_ref::<String>() {
+        Some(message) => message.clone(),
+        None => "unknown panic".to_owned(),
+      },
+    };
+
+    let location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+
+    handle_panic(&message, location.as_deref().unwrap_or("unknown location"));
+
+    previous_hook(info);
+  }));
+}
+
+/// Sets a named section of information to include in crash reports, such as
+/// settings or hardware details, replacing any section with the same name.
+pub fn set_info(name: impl Into<String>, text: impl Into<String>) {
+  INFO.lock().insert(name.into(), text.into());
+}
+
+/// Logs a panic and writes a crash report if a handler is installed.
+fn handle_panic(message: &str, location: &str) {
+  // Never block indefinitely in the panic hook in case the panic happened
+  // while a lock was held on this thread. Copy the handler so that panics on
+  // other threads are not blocked while the report is written.
+  let handler = match HANDLER.try_lock_for(std::time::Duration::from_millis(100)) {
+    Some(handler) => match handler.as_ref() {
+      Some(handler) => handler.clone(),
+      None => return,
+    },
+
+    None => return,
+  };
+
+  let thread = thread::current();
+  let thread = thread.name().unwrap_or("unnamed");
+  let backtrace = Backtrace::force_capture();
+
+  let logger = log::try_logger();
+
+  if let Some(logger) = &logger {
+    log::crit!(logger, "thread panicked";
+      "thread" => thread,
+      "message" => message,
+      "location" => location,
+      "backtrace" => log::Display(&backtrace),
+    );
+  }
+
+  let now = chrono::Local::now();
+  let count = REPORT_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
+  let name = format!("crash-{}-{}.txt", now.format("%Y%m%d-%H%M%S%.3f"), count);
+  let path = handler.dir.join(name);
+
+  let mut report = String::new();
+
+  report.push_str("# Crash report\n\n");
+  report.push_str(&format!("time: {}\n", now.to_rfc3339()));
+  report.push_str(&format!("thread: {}\n", thread));
+  report.push_str(&format!("message: {}\n", message));
+  report.push_str(&format!("location: {}\n", location));
+
+  report.push_str("\n## Build\n\n");
+  report.push_str(&format!("package: {} {}\n", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+  report
+    .push_str(&format!("profile: {}\n", if cfg!(debug_assertions) { "debug" } else { "release" }));
+
+  report.push_str("\n## System\n\n");
+  report.push_str(&format!("os: {} ({})\n", env::consts::OS, env::consts::FAMILY));
+  report.push_str(&format!("arch: {}\n", env::consts::ARCH));
+
+  if let Some(info) = INFO.try_lock() {
+    for (name, text) in info.iter() {
+      report.push_str(&format!("\n## {}\n\n{}\n", name, text.trim_end()));
+    }
+  }
+
+  report.push_str("\n## Recent log records\n\n");
+
+  for record in log::recent() {
+    report.push_str(&format!("{}\n", record));
+  }
+
+  report.push_str(&format!("\n## Backtrace\n\n{}\n", backtrace));
+
+  let result = handler.vfs.create(&path).and_then(|mut file| file.write_all(report.as_bytes()));
+
+  if let Some(logger) = &logger {
+    match result {
+      Ok(()) => log::crit!(logger, "wrote crash report"; "path" => log::Display(&path)),
+      Err(err) => log::crit!(logger, "could not write crash report: {}", err),
+    }
+  }
+
+  log::flush(time::seconds(1.0));
+}
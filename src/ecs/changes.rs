@@ -0,0 +1,215 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+pub use super::storage::{ComponentEvent, FlaggedStorage, Tracked};
+
+use super::*;
+use std::marker::PhantomData;
+
+/// A cursor for reading the changes to the components of type `C`.
+///
+/// Changes can only be tracked for components that use `FlaggedStorage`.
+/// Create a reader with `ComponentsMut::register_change_reader()` and pass
+/// it to `changed_since()` to get the changes since the previous call.
+pub struct ChangeReaderId<C> {
+  id: specs::ReaderId<ComponentEvent>,
+  changed: Changed<C>,
+}
+
+impl<C> ChangeReaderId<C> {
+  pub(super) fn new(id: specs::ReaderId<ComponentEvent>) -> Self {
+    Self { id, changed: Changed::default() }
+  }
+
+  /// Replaces the changes in the reader with the given events.
+  pub(super) fn read<'e, F, I>(&mut self, read_events: F) -> &Changed<C>
+  where
+    F: FnOnce(&mut specs::ReaderId<ComponentEvent>) -> I,
+    I: Iterator<Item = &'e ComponentEvent>,
+  {
+    let changed = &mut self.changed;
+
+    changed.inserted.clear();
+    changed.modified.clear();
+    changed.removed.clear();
+
+    for event in read_events(&mut self.id) {
+      match event {
+        ComponentEvent::Inserted(id) => changed.inserted.add(*id),
+        ComponentEvent::Modified(id) => changed.modified.add(*id),
+        ComponentEvent::Removed(id) => changed.removed.add(*id),
+      };
+    }
+
+    changed
+  }
+}
+
+/// The entities whose components of type `C` changed since the previous time
+/// a `ChangeReaderId<C>` was read.
+///
+/// A component is considered modified whenever it is accessed mutably, even
+/// if its value does not change. An entity can be in more than one set if
+/// several kinds of changes happened, such as when a component is removed and
+/// then inserted again.
+///
+/// Joining over a `&Changed<C>` yields the index of each entity with an
+/// inserted or modified component, so it can be used to filter other joins.
+pub struct Changed<C> {
+  inserted: BitSet,
+  modified: BitSet,
+  removed: BitSet,
+  marker: PhantomData<fn() -> C>,
+}
+
+impl<C> Default for Changed<C> {
+  fn default() -> Self {
+    Self {
+      inserted: BitSet::new(),
+      modified: BitSet::new(),
+      removed: BitSet::new(),
+      marker: PhantomData,
+    }
+  }
+}
+
+impl<C> Changed<C> {
+  /// Returns the set of entity indices with inserted components.
+  pub fn inserted(&self) -> &BitSet {
+    &self.inserted
+  }
+
+  /// Returns the set of entity indices with modified components.
+  pub fn modified(&self) -> &BitSet {
+    &self.modified
+  }
+
+  /// Returns the set of entity indices with removed components.
+  pub fn removed(&self) -> &BitSet {
+    &self.removed
+  }
+
+  /// Returns `true` if the component of the given entity was inserted or
+  /// modified.
+  pub fn contains(&self, entity: Entity) -> bool {
+    self.inserted.contains(entity.id()) || self.modified.contains(entity.id())
+  }
+
+  /// Returns `true` if no components were inserted, modified, or removed.
+  pub fn is_empty(&self) -> bool {
+    self.inserted.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+  }
+}
+
+impl<'a, C> Join for &'a Changed<C> {
+  type Type = specs::world::Index;
+  type Value = ();
+  type Mask = BitSetOr<&'a BitSet, &'a BitSet>;
+
+  unsafe fn open(self) -> (Self::Mask, Self::Value) {
+    (BitSetOr(&self.inserted, &self.modified), ())
+  }
+
+  unsafe fn get(_: &mut Self::Value, index: specs::world::Index) -> Self::Type {
+    index
+  }
+}
+
+unsafe impl<C> ParJoin for &Changed<C> {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ecs::storage::DenseVecStorage;
+
+  #[derive(Debug, PartialEq)]
+  struct Position(i32);
+
+  impl Component for Position {
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+  }
+
+  /// Returns the entity indices in a bit set in order.
+  fn indices(set: &BitSet) -> Vec<specs::world::Index> {
+    set.join().collect()
+  }
+
+  #[test]
+  fn readers_see_inserted_modified_and_removed_components() {
+    let mut ctx = Context::new();
+
+    ctx.register_component::<Position>();
+
+    let [a, b, c] = [(); 3].map(|_| ctx.entities().create());
+
+    ctx.commit_entities();
+
+    let mut reader = ctx.register_change_reader::<Position>();
+
+    {
+      let mut positions = ctx.components_mut();
+
+      positions.insert(a, Position(1));
+      positions.insert(b, Position(2));
+      positions.insert(c, Position(3));
+    }
+
+    {
+      let positions = ctx.components::<Position>();
+      let changed = positions.changed_since(&mut reader);
+
+      assert_eq!(indices(changed.inserted()), [a.id(), b.id(), c.id()]);
+      assert!(changed.modified().is_empty() && changed.removed().is_empty());
+      assert!(positions.changed_since(&mut reader).is_empty());
+    }
+
+    {
+      let mut positions = ctx.components_mut::<Position>();
+
+      positions.get_mut(b).unwrap().0 += 10;
+      positions.remove(c);
+    }
+
+    let positions = ctx.components::<Position>();
+    let changed = positions.changed_since(&mut reader);
+
+    assert!(changed.inserted().is_empty());
+    assert_eq!(indices(changed.modified()), [b.id()]);
+    assert_eq!(indices(changed.removed()), [c.id()]);
+    assert!(!changed.contains(a) && changed.contains(b) && !changed.contains(c));
+    assert_eq!((changed, &positions).join().map(|(_, p)| p).collect::<Vec<_>>(), [&Position(12)]);
+  }
+
+  #[test]
+  fn readers_only_see_changes_made_after_they_are_registered() {
+    let mut ctx = Context::new();
+
+    ctx.register_component::<Position>();
+
+    let entity = ctx.entities().create();
+
+    ctx.commit_entities();
+    ctx.components_mut().insert(entity, Position(0));
+
+    let mut reader = ctx.register_change_reader::<Position>();
+
+    assert!(ctx.components_mut::<Position>().changed_since(&mut reader).is_empty());
+
+    ctx.components_mut().insert(entity, Position(1));
+
+    let mut positions = ctx.components_mut::<Position>();
+    let changed = positions.changed_since(&mut reader);
+
+    // Replacing a component is reported as a modification.
+    assert_eq!(indices(changed.modified()), [entity.id()]);
+    assert!(changed.inserted().is_empty());
+
+    positions.remove(entity);
+    positions.insert(entity, Position(2));
+
+    let changed = positions.changed_since(&mut reader);
+
+    assert!(changed.inserted().contains(entity.id()) && changed.removed().contains(entity.id()));
+  }
+}
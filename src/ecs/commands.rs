@@ -0,0 +1,209 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use crossbeam_queue::SegQueue;
+use specs::storage::MaskedStorage;
+
+/// Provides deferred access to the entities and components in an instance.
+///
+/// Operations are recorded without mutable access, so `Commands` can be used
+/// from parallel systems and joins. Recorded operations are applied when
+/// `Context::commit_entities()` is called, just before entities are
+/// committed.
+///
+/// Operations recorded by one system, or by systems that run one after the
+/// other such as with `Context::schedule_after()`, are applied in the order
+/// they were recorded. Operations recorded by systems running in parallel may
+/// be interleaved in any order, so such systems should not record conflicting
+/// operations on the same entity.
+pub struct Commands<'a> {
+  entities: specs::Entities<'a>,
+  queue: specs::ReadExpect<'a, CommandQueue>,
+  res: &'a specs::Resources,
+}
+
+/// The system data fetched by `Commands` other than the resources themselves,
+/// which are only used to check that component types are registered.
+type CommandsData<'a> = (specs::Entities<'a>, specs::ReadExpect<'a, CommandQueue>);
+
+impl<'a> SystemData<'a> for Commands<'a> {
+  fn setup(res: &mut specs::Resources) {
+    CommandsData::setup(res);
+  }
+
+  fn fetch(res: &'a specs::Resources) -> Self {
+    let (entities, queue) = CommandsData::fetch(res);
+
+    Commands { entities, queue, res }
+  }
+
+  fn reads() -> Vec<shred::ResourceId> {
+    CommandsData::reads()
+  }
+
+  fn writes() -> Vec<shred::ResourceId> {
+    CommandsData::writes()
+  }
+}
+
+impl<'a> Commands<'a> {
+  /// Creates a new entity.
+  ///
+  /// The entity is created immediately so that components can be inserted for
+  /// it, but like other new entities it is only committed by
+  /// `Context::commit_entities()`.
+  pub fn create(&self) -> Entity {
+    self.entities.create()
+  }
+
+  /// Records the deletion of the given entity.
+  pub fn delete(&self, entity: Entity) {
+    self.queue.push(move |world| {
+      let _ = world.entities().delete(entity);
+    });
+  }
+
+  /// Records setting the component of type `C` for the given entity.
+  ///
+  /// If the entity is not alive when the operation is applied, the component
+  /// is dropped.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the component type `C` is not registered.
+  pub fn insert<C: Component + Send + Sync>(&self, entity: Entity, value: C) {
+    self.expect_registered::<C>();
+
+    self.queue.push(move |world| {
+      let _ = world.write_storage::<C>().insert(entity, value);
+    });
+  }
+
+  /// Records the removal of the component of type `C` for the given entity.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the component type `C` is not registered.
+  pub fn remove<C: Component + Send + Sync>(&self, entity: Entity) {
+    self.expect_registered::<C>();
+
+    self.queue.push(move |world| {
+      world.write_storage::<C>().remove(entity);
+    });
+  }
+
+  /// Panics if the component type `C` is not registered, so that the panic
+  /// happens where the operation is recorded instead of where it is applied.
+  fn expect_registered<C: Component>(&self) {
+    if !self.res.has_value::<MaskedStorage<C>>() {
+      panic!("component type {} is not registered", std::any::type_name::<C>());
+    }
+  }
+}
+
+/// An operation recorded with `Commands`.
+type Command = Box<dyn FnOnce(&mut specs::World) + Send>;
+
+/// A resource containing the operations recorded with `Commands`.
+#[derive(Default)]
+pub(super) struct CommandQueue(SegQueue<Command>);
+
+impl CommandQueue {
+  /// Adds an operation to the end of the queue.
+  fn push(&self, command: impl FnOnce(&mut specs::World) + Send + 'static) {
+    self.0.push(Box::new(command));
+  }
+}
+
+/// Applies all operations recorded with `Commands` in order, including any
+/// recorded while applying them.
+pub(super) fn apply(world: &mut specs::World) {
+  loop {
+    let command = match world.read_resource::<CommandQueue>().0.pop() {
+      Ok(command) => command,
+      Err(_) => return,
+    };
+
+    command(world);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use specs::storage::DenseVecStorage;
+
+  #[derive(Debug, PartialEq)]
+  struct Health(u32);
+
+  impl Component for Health {
+    type Storage = DenseVecStorage<Self>;
+  }
+
+  /// A system that records inserting a `Health` component for the entity.
+  struct Heal(Entity, u32);
+
+  impl<'a> System<'a> for Heal {
+    type SystemData = Commands<'a>;
+
+    fn run(&mut self, commands: Self::SystemData) {
+      commands.insert(self.0, Health(self.1));
+    }
+  }
+
+  #[test]
+  fn commands_are_applied_in_the_order_recorded() {
+    let mut ctx = Context::new();
+
+    ctx.register_component::<Health>();
+
+    let kept = ctx.commands().create();
+    let deleted = ctx.commands().create();
+
+    {
+      let commands = ctx.commands();
+
+      commands.insert(kept, Health(1));
+      commands.remove::<Health>(kept);
+      commands.insert(kept, Health(2));
+      commands.insert(deleted, Health(3));
+      commands.delete(deleted);
+    }
+
+    assert_eq!(ctx.components::<Health>().get(kept), None);
+
+    ctx.commit_entities();
+
+    assert_eq!(ctx.components::<Health>().get(kept), Some(&Health(2)));
+    assert!(!ctx.entities().is_alive(deleted));
+  }
+
+  #[test]
+  fn commands_of_dependent_systems_are_applied_in_system_order() {
+    let mut ctx = Context::new();
+
+    ctx.register_component::<Health>();
+
+    let entity = ctx.entities().create();
+
+    ctx.commit_entities();
+    ctx.schedule_after(Phase::Update, "first", &[], Heal(entity, 1));
+    ctx.schedule_after(Phase::Update, "second", &["first"], Heal(entity, 2));
+    ctx.schedule_after(Phase::Update, "third", &["second"], Heal(entity, 3));
+    ctx.run_phase(Phase::Update);
+    ctx.commit_entities();
+
+    assert_eq!(ctx.components::<Health>().get(entity), Some(&Health(3)));
+  }
+
+  #[test]
+  #[should_panic(expected = "is not registered")]
+  fn recording_an_unregistered_component_panics() {
+    let ctx = Context::new();
+    let entity = ctx.entities().create();
+
+    ctx.commands().insert(entity, Health(1));
+  }
+}
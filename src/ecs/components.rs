@@ -4,7 +4,7 @@
 
 pub use specs::{Component, Join, ParJoin};
 
-use super::storage::UnprotectedStorage;
+use super::storage::{DistinctStorage, UnprotectedStorage};
 use super::*;
 
 /// Provides read-only access to the components of type `C` in an instance.
@@ -17,6 +17,22 @@ impl<'a, C: Component> Components<'a, C> {
   pub fn get(&self, entity: Entity) -> Option<&C> {
     self.0.get(entity)
   }
+
+  /// Returns the set of entity indices with a component.
+  pub(super) fn mask(&self) -> &BitSet {
+    self.0.mask()
+  }
+}
+
+impl<'a, C: Component> Components<'a, C>
+where
+  C::Storage: Tracked,
+{
+  /// Returns the entities whose components changed since the previous call
+  /// with the given reader.
+  pub fn changed_since<'r>(&self, reader: &'r mut ChangeReaderId<C>) -> &'r Changed<C> {
+    reader.read(|id| self.0.channel().read(id))
+  }
 }
 
 impl<'a, C: Component> Join for &'a Components<'a, C> {
@@ -33,6 +49,8 @@ impl<'a, C: Component> Join for &'a Components<'a, C> {
   }
 }
 
+unsafe impl<'a, C: Component> ParJoin for &'a Components<'a, C> where C::Storage: Sync {}
+
 /// Provides read and write access to the components of type `C` in an instance.
 #[derive(SystemData)]
 pub struct ComponentsMut<'a, C: Component>(specs::WriteStorage<'a, C>);
@@ -67,8 +85,46 @@ impl<'a, C: Component> ComponentsMut<'a, C> {
   pub fn remove(&mut self, entity: Entity) -> Option<C> {
     self.0.remove(entity)
   }
+
+  /// Returns the set of entity indices with a component.
+  pub(super) fn mask(&self) -> &BitSet {
+    self.0.mask()
+  }
+}
+
+impl<'a, C: Component> ComponentsMut<'a, C>
+where
+  C::Storage: Tracked,
+{
+  /// Returns a new reader for the changes to the components made after this
+  /// call.
+  pub fn register_change_reader(&mut self) -> ChangeReaderId<C> {
+    ChangeReaderId::new(self.0.register_reader())
+  }
+
+  /// Returns the entities whose components changed since the previous call
+  /// with the given reader.
+  pub fn changed_since<'r>(&self, reader: &'r mut ChangeReaderId<C>) -> &'r Changed<C> {
+    reader.read(|id| self.0.channel().read(id))
+  }
+}
+
+impl<'a, 'b, C: Component> Join for &'a ComponentsMut<'b, C> {
+  type Type = &'a C;
+  type Value = &'a C::Storage;
+  type Mask = &'a BitSet;
+
+  unsafe fn open(self) -> (Self::Mask, Self::Value) {
+    (&self.0).open()
+  }
+
+  unsafe fn get(value: &mut Self::Value, index: specs::world::Index) -> Self::Type {
+    value.get(index)
+  }
 }
 
+unsafe impl<'a, 'b, C: Component> ParJoin for &'a ComponentsMut<'b, C> where C::Storage: Sync {}
+
 impl<'a, 'b, C: Component> Join for &'a mut ComponentsMut<'b, C> {
   type Type = &'a mut C;
   type Value = &'a mut C::Storage;
@@ -84,3 +140,8 @@ impl<'a, 'b, C: Component> Join for &'a mut ComponentsMut<'b, C> {
     (*value).get_mut(index)
   }
 }
+
+unsafe impl<'a, 'b, C: Component> ParJoin for &'a mut ComponentsMut<'b, C> where
+  C::Storage: Sync + DistinctStorage
+{
+}
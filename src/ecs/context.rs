@@ -2,12 +2,25 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use super::events::{EventChannel, EventReaderId};
 use super::*;
 
-/// A standalone ECS context containing resources, entities, and components.
-#[derive(Default)]
+/// A standalone ECS context containing resources, entities, components, and
+/// the systems scheduled to run on them.
 pub struct Context {
   world: specs::World,
+  schedule: Schedule,
+  persistent_types: Vec<PersistentType>,
+}
+
+impl Default for Context {
+  fn default() -> Self {
+    let mut world = specs::World::new();
+
+    world.add_resource(CommandQueue::default());
+
+    Self { world, schedule: Schedule::default(), persistent_types: Vec::new() }
+  }
 }
 
 impl Context {
@@ -42,11 +55,20 @@ impl Context {
     self.world.system_data()
   }
 
-  /// Finalizes the creation and deletion of any entities created or deleted
-  /// since the previous call to this function.
+  /// Returns a `Commands` struct for recording operations on entities and
+  /// components that are applied by `commit_entities()`.
+  pub fn commands(&self) -> Commands<'_> {
+    self.world.system_data()
+  }
+
+  /// Applies the operations recorded with `Commands`, then finalizes the
+  /// creation and deletion of any entities created or deleted since the
+  /// previous call to this function.
   ///
   /// A deleted entity is still considered alive until this function is called.
   pub fn commit_entities(&mut self) {
+    commands::apply(&mut self.world);
+
     self.world.maintain();
   }
 
@@ -58,6 +80,68 @@ impl Context {
     self.world.register::<C>();
   }
 
+  /// Registers a possible component type that is saved in snapshots.
+  ///
+  /// # Panics
+  ///
+  /// Panics if a persistent component with the same name is already
+  /// registered.
+  pub fn register_persistent_component<C: Component + Persistent>(&mut self)
+  where
+    C::Storage: Default,
+  {
+    self.register_component::<C>();
+    self.add_persistent_type(PersistentType::component::<C>());
+  }
+
+  /// Registers a resource type that is saved in snapshots if it exists.
+  ///
+  /// # Panics
+  ///
+  /// Panics if a persistent resource with the same name is already registered.
+  pub fn register_persistent_resource<R: ResourceLike + Persistent>(&mut self) {
+    self.add_persistent_type(PersistentType::resource::<R>());
+  }
+
+  /// Saves all entities and the values of all persistent components and
+  /// resources to a new snapshot.
+  pub fn save_snapshot(&self) -> Result<Snapshot, SnapshotError> {
+    snapshot::save(&self.world, &self.persistent_types)
+  }
+
+  /// Deletes all entities and restores the entities, persistent components,
+  /// and persistent resources saved in the given snapshot.
+  ///
+  /// Returns the restored entities in the order in which they are listed in
+  /// the snapshot. Entity references in restored components refer to the
+  /// restored entities.
+  pub fn load_snapshot(&mut self, snapshot: &Snapshot) -> Result<Vec<Entity>, SnapshotError> {
+    snapshot::load(&mut self.world, &self.persistent_types, snapshot)
+  }
+
+  /// Adds an `EventChannel<T>` resource for events of type `T` if one does
+  /// not already exist.
+  pub fn add_event_channel<T: Send + Sync + 'static>(&mut self) {
+    if !self.world.res.has_value::<EventChannel<T>>() {
+      self.put_resource(EventChannel::<T>::new());
+    }
+  }
+
+  /// Returns a new reader for the `EventChannel<T>` resource that reads events
+  /// written after this call.
+  ///
+  /// Panics if no such resource exists.
+  pub fn register_event_reader<T: Send + Sync + 'static>(&self) -> EventReaderId<T> {
+    self.resource::<EventChannel<T>>().register_reader()
+  }
+
+  /// Writes an event to the `EventChannel<T>` resource.
+  ///
+  /// Panics if no such resource exists.
+  pub fn write_event<T: Send + Sync + 'static>(&self, event: T) {
+    self.resource_mut::<EventChannel<T>>().single_write(event);
+  }
+
   /// Returns a `Components<C>` struct for reading the components of type `C`
   /// for all entities.
   pub fn components<C: Component>(&self) -> Components<C> {
@@ -69,4 +153,80 @@ impl Context {
   pub fn components_mut<C: Component>(&self) -> ComponentsMut<C> {
     self.world.system_data()
   }
+
+  /// Returns a new reader for the changes to the components of type `C` made
+  /// after this call.
+  pub fn register_change_reader<C: Component>(&self) -> ChangeReaderId<C>
+  where
+    C::Storage: Tracked,
+  {
+    self.components_mut::<C>().register_change_reader()
+  }
+
+  /// Schedules a system to run during the given phase.
+  ///
+  /// The system may run in parallel with other systems in the same phase if
+  /// they do not access the same resources or components mutably.
+  pub fn schedule<S>(&mut self, phase: Phase, system: S)
+  where
+    S: for<'a> System<'a> + Send + 'static,
+  {
+    self.schedule.add(phase, "", &[], system);
+  }
+
+  /// Schedules a named system to run during the given phase after each of the
+  /// named `dependencies` in the same phase.
+  ///
+  /// # Panics
+  ///
+  /// Panics if a system named `name` is already scheduled in the phase or if
+  /// any of the `dependencies` are not scheduled in the phase.
+  pub fn schedule_after<S>(&mut self, phase: Phase, name: &str, dependencies: &[&str], system: S)
+  where
+    S: for<'a> System<'a> + Send + 'static,
+  {
+    self.schedule.add(phase, name, dependencies, system);
+  }
+
+  /// Schedules a system to run sequentially on the thread calling
+  /// `run_phase()` during the given phase.
+  ///
+  /// Use this function for systems that cannot be sent between threads.
+  /// Thread-local systems run after all of the parallel systems scheduled in
+  /// the phase since it last ran, including those scheduled after them.
+  pub fn schedule_seq<S>(&mut self, phase: Phase, system: S)
+  where
+    S: for<'a> System<'a> + 'static,
+  {
+    self.schedule.add_thread_local(phase, system);
+  }
+
+  /// Runs all systems scheduled in the given phase.
+  ///
+  /// Systems scheduled since the previous run of the phase are set up before
+  /// running, which registers the resources and components they use.
+  pub fn run_phase(&mut self, phase: Phase) {
+    self.schedule.run(phase, &mut self.world.res);
+  }
+
+  /// Runs the systems scheduled in each phase in order.
+  pub fn run_phases(&mut self) {
+    for phase in Phase::ALL.iter().cloned() {
+      self.run_phase(phase);
+    }
+  }
+
+  /// Adds a persistent type, panicking if its name is already used.
+  fn add_persistent_type(&mut self, ty: PersistentType) {
+    let duplicate = self
+      .persistent_types
+      .iter()
+      .any(|other| other.name() == ty.name() && other.is_component() == ty.is_component());
+
+    if duplicate {
+      panic!("a persistent type named {:?} is already registered", ty.name());
+    }
+
+    self.persistent_types.push(ty);
+  }
 }
@@ -49,3 +49,5 @@ impl<'a, 'b> Join for &'a Entities<'b> {
     Self::Value::get(this, idx)
   }
 }
+
+unsafe impl<'a, 'b> ParJoin for &'a Entities<'b> {}
@@ -0,0 +1,225 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Typed event channels that can be stored as ECS resources.
+//!
+//! An `EventChannel<T>` stores the most recently written events of type `T`
+//! in a ring buffer. Each reader has its own `EventReaderId<T>` cursor, so
+//! any number of systems can read every event independently. Readers that
+//! fall behind by more than the capacity of the channel miss the oldest
+//! events, which is reported by `EventIter::missed()`.
+
+use std::marker::PhantomData;
+use std::{fmt, iter};
+
+/// The number of events an `EventChannel` created with `new()` can hold.
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// A ring buffer of events of type `T` with independent readers.
+pub struct EventChannel<T> {
+  buffer: Vec<T>,
+  capacity: usize,
+  /// Total number of events ever written, which is also the sequence number
+  /// of the next event.
+  written: u64,
+}
+
+impl<T> Default for EventChannel<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T> EventChannel<T> {
+  /// Creates a new channel that holds up to `DEFAULT_CAPACITY` events.
+  pub fn new() -> Self {
+    Self::with_capacity(DEFAULT_CAPACITY)
+  }
+
+  /// Creates a new channel that holds up to `capacity` events.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `capacity` is zero.
+  pub fn with_capacity(capacity: usize) -> Self {
+    assert!(capacity > 0, "event channel capacity must be greater than zero");
+
+    Self { buffer: Vec::new(), capacity, written: 0 }
+  }
+
+  /// Returns the maximum number of events the channel holds before the
+  /// oldest events are overwritten.
+  pub fn capacity(&self) -> usize {
+    self.capacity
+  }
+
+  /// Returns the total number of events ever written to the channel.
+  pub fn written(&self) -> u64 {
+    self.written
+  }
+
+  /// Returns a new reader that reads events written after this call.
+  pub fn register_reader(&self) -> EventReaderId<T> {
+    EventReaderId { next: self.written, marker: PhantomData }
+  }
+
+  /// Writes a single event to the channel, overwriting the oldest event if
+  /// the channel is full.
+  pub fn single_write(&mut self, event: T) {
+    if self.buffer.len() < self.capacity {
+      self.buffer.push(event);
+    } else {
+      let index = self.index(self.written);
+
+      self.buffer[index] = event;
+    }
+
+    self.written += 1;
+  }
+
+  /// Writes each event from an iterator to the channel in order.
+  pub fn iter_write(&mut self, events: impl IntoIterator<Item = T>) {
+    for event in events {
+      self.single_write(event);
+    }
+  }
+
+  /// Writes all events in a `Vec` to the channel in order, leaving it empty.
+  pub fn drain_vec_write(&mut self, events: &mut Vec<T>) {
+    self.iter_write(events.drain(..));
+  }
+
+  /// Returns an iterator over the events the given reader has not read yet
+  /// and moves the reader to the end of the channel.
+  pub fn read(&self, reader: &mut EventReaderId<T>) -> EventIter<'_, T> {
+    let oldest = self.written - self.buffer.len() as u64;
+    let start = reader.next.clamp(oldest, self.written);
+
+    let iter = EventIter {
+      channel: self,
+      next: start,
+      end: self.written,
+      missed: start.saturating_sub(reader.next),
+    };
+
+    reader.next = self.written;
+
+    iter
+  }
+
+  /// Returns the index in the buffer of the event with the given sequence
+  /// number.
+  fn index(&self, sequence: u64) -> usize {
+    (sequence % self.capacity as u64) as usize
+  }
+}
+
+impl<T> fmt::Debug for EventChannel<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("EventChannel")
+      .field("capacity", &self.capacity)
+      .field("written", &self.written)
+      .finish()
+  }
+}
+
+/// A cursor into an `EventChannel<T>` for reading its events.
+pub struct EventReaderId<T> {
+  /// Sequence number of the next event to read.
+  next: u64,
+  marker: PhantomData<fn() -> T>,
+}
+
+impl<T> fmt::Debug for EventReaderId<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("EventReaderId").field("next", &self.next).finish()
+  }
+}
+
+/// An iterator over the unread events in an `EventChannel<T>`.
+pub struct EventIter<'a, T> {
+  channel: &'a EventChannel<T>,
+  next: u64,
+  end: u64,
+  missed: u64,
+}
+
+impl<'a, T> EventIter<'a, T> {
+  /// Returns the number of events the reader missed because they were
+  /// overwritten before it read them.
+  pub fn missed(&self) -> u64 {
+    self.missed
+  }
+}
+
+impl<'a, T> Iterator for EventIter<'a, T> {
+  type Item = &'a T;
+
+  fn next(&mut self) -> Option<&'a T> {
+    if self.next == self.end {
+      return None;
+    }
+
+    let event = &self.channel.buffer[self.channel.index(self.next)];
+
+    self.next += 1;
+
+    Some(event)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let len = (self.end - self.next) as usize;
+
+    (len, Some(len))
+  }
+}
+
+impl<'a, T> ExactSizeIterator for EventIter<'a, T> {}
+
+impl<'a, T> iter::FusedIterator for EventIter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn readers_read_every_event_independently() {
+    let mut channel = EventChannel::with_capacity(4);
+    let mut first = channel.register_reader();
+
+    channel.iter_write([1, 2]);
+
+    let mut second = channel.register_reader();
+
+    channel.single_write(3);
+
+    let events = channel.read(&mut first);
+
+    assert_eq!(events.missed(), 0);
+    assert_eq!(events.collect::<Vec<_>>(), [&1, &2, &3]);
+    assert_eq!(channel.read(&mut second).collect::<Vec<_>>(), [&3]);
+    assert_eq!(channel.read(&mut first).count(), 0);
+  }
+
+  #[test]
+  fn readers_that_fall_behind_miss_the_oldest_events() {
+    let mut channel = EventChannel::with_capacity(3);
+    let mut reader = channel.register_reader();
+
+    channel.iter_write(1..=5);
+
+    let events = channel.read(&mut reader);
+
+    assert_eq!(events.missed(), 2);
+    assert_eq!(events.collect::<Vec<_>>(), [&3, &4, &5]);
+
+    channel.drain_vec_write(&mut vec![6, 7, 8, 9]);
+
+    let events = channel.read(&mut reader);
+
+    assert_eq!(events.missed(), 1);
+    assert_eq!(events.len(), 3);
+    assert_eq!(channel.written(), 9);
+  }
+}
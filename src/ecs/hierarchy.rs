@@ -0,0 +1,262 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::storage::{ComponentEvent, DenseVecStorage, FlaggedStorage};
+use super::*;
+use serde_derive::*;
+use specs::ReaderId;
+use std::collections::HashMap;
+use std::ops::Deref;
+
+/// A component that makes an entity the child of another entity.
+///
+/// When the parent is deleted, the child and all of its descendants are
+/// deleted too. A parent that would make an entity its own ancestor is
+/// removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Parent(#[serde(with = "entity_ref")] pub Entity);
+
+impl Component for Parent {
+  type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+impl Persistent for Parent {
+  const NAME: &'static str = "parent";
+}
+
+/// A component listing the children of an entity in the order they were
+/// added.
+///
+/// This component is maintained by the `MaintainHierarchy` system from the
+/// `Parent` components of other entities and should not be modified directly.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Children(Vec<Entity>);
+
+impl Component for Children {
+  type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+impl Deref for Children {
+  type Target = [Entity];
+
+  fn deref(&self) -> &[Entity] {
+    &self.0
+  }
+}
+
+/// A system that maintains `Children` components and deletes the descendants
+/// of deleted entities.
+#[derive(Default)]
+pub struct MaintainHierarchy {
+  parent_reader: Option<ReaderId<ComponentEvent>>,
+  children_reader: Option<ReaderId<ComponentEvent>>,
+  /// The child and parent entities last seen for each entity index with a
+  /// `Parent` component.
+  parents: HashMap<specs::world::Index, (Entity, Entity)>,
+  /// The parent entity and its children for each entity index with a
+  /// `Children` component, kept so the children of a deleted parent can be
+  /// found after its `Children` component is gone.
+  children: HashMap<specs::world::Index, (Entity, Vec<Entity>)>,
+  changed: BitSet,
+}
+
+impl MaintainHierarchy {
+  /// Returns the name the system is scheduled with by `set_up_hierarchy()`.
+  pub fn name() -> &'static str {
+    "ecs::MaintainHierarchy"
+  }
+
+  /// Returns `true` if `ancestor` is `entity` or one of its ancestors.
+  fn is_ancestor(&self, ancestor: Entity, mut entity: Entity) -> bool {
+    loop {
+      if entity == ancestor {
+        return true;
+      }
+
+      match self.parents.get(&entity.id()) {
+        Some((child, parent)) if *child == entity => entity = *parent,
+        _ => return false,
+      }
+    }
+  }
+}
+
+impl<'a> System<'a> for MaintainHierarchy {
+  type SystemData =
+    (specs::Entities<'a>, specs::WriteStorage<'a, Parent>, specs::WriteStorage<'a, Children>);
+
+  fn setup(&mut self, res: &mut specs::Resources) {
+    Self::SystemData::setup(res);
+
+    self.parent_reader = Some(specs::WriteStorage::<Parent>::fetch(res).register_reader());
+    self.children_reader = Some(specs::WriteStorage::<Children>::fetch(res).register_reader());
+  }
+
+  fn run(&mut self, (entities, mut parents, mut children): Self::SystemData) {
+    let parent_reader = self.parent_reader.as_mut().expect("system was not set up");
+    let children_reader = self.children_reader.as_mut().expect("system was not set up");
+
+    self.changed.clear();
+
+    for event in parents.channel().read(parent_reader) {
+      match event {
+        ComponentEvent::Inserted(id)
+        | ComponentEvent::Modified(id)
+        | ComponentEvent::Removed(id) => {
+          self.changed.add(*id);
+        }
+      }
+    }
+
+    // Children of deleted parents are orphaned. Deleting an entity removes its
+    // `Children` component, so removal events with a dead parent mark them.
+    let mut orphans = Vec::new();
+
+    for event in children.channel().read(children_reader) {
+      if let ComponentEvent::Removed(id) = event {
+        let deleted = match self.children.get(id) {
+          Some((parent, _)) => !entities.is_alive(*parent),
+          None => false,
+        };
+
+        if deleted {
+          if let Some((_, list)) = self.children.remove(id) {
+            orphans.extend(list);
+          }
+        }
+      }
+    }
+
+    for id in (&self.changed).join() {
+      let entity = entities.entity(id);
+      let old = self.parents.get(&id).cloned();
+      let new = parents.get(entity).map(|parent| (entity, parent.0));
+
+      if old == new {
+        continue;
+      }
+
+      if let Some((old_child, old_parent)) = old {
+        self.parents.remove(&id);
+
+        let now_empty = match self.children.get_mut(&old_parent.id()) {
+          Some((parent, list)) if *parent == old_parent => {
+            list.retain(|child| *child != old_child);
+            list.is_empty()
+          }
+
+          _ => false,
+        };
+
+        if now_empty {
+          self.children.remove(&old_parent.id());
+          children.remove(old_parent);
+        } else if let Some(list) = children.get_mut(old_parent) {
+          list.0.retain(|child| *child != old_child);
+        }
+      }
+
+      if let Some((child, parent)) = new {
+        if !entities.is_alive(parent) {
+          orphans.push(child);
+          continue;
+        }
+
+        if self.is_ancestor(child, parent) {
+          parents.remove(child);
+          continue;
+        }
+
+        self.parents.insert(id, (child, parent));
+
+        match self.children.get_mut(&parent.id()) {
+          Some((entry, list)) if *entry == parent => list.push(child),
+
+          _ => {
+            self.children.insert(parent.id(), (parent, vec![child]));
+          }
+        }
+
+        match children.get_mut(parent) {
+          Some(list) => list.0.push(child),
+
+          None => {
+            let _ = children.insert(parent, Children(vec![child]));
+          }
+        }
+      }
+    }
+
+    // Delete the subtrees of orphaned children.
+    while let Some(entity) = orphans.pop() {
+      let _ = entities.delete(entity);
+
+      if let Some((_, list)) = self.children.remove(&entity.id()) {
+        orphans.extend(list);
+      }
+    }
+  }
+}
+
+/// Registers the `Parent` and `Children` components, making `Parent`
+/// persistent, and schedules the `MaintainHierarchy` system in the
+/// `AfterUpdate` phase.
+///
+/// Children of a deleted entity are deleted the next time the system runs.
+pub fn set_up_hierarchy(ctx: &mut Context) {
+  ctx.register_persistent_component::<Parent>();
+  ctx.register_component::<Children>();
+  ctx.schedule_after(
+    Phase::AfterUpdate,
+    MaintainHierarchy::name(),
+    &[],
+    MaintainHierarchy::default(),
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn deleting_a_parent_deletes_its_subtree() {
+    let mut ctx = Context::new();
+
+    set_up_hierarchy(&mut ctx);
+
+    // Set up the system so that it reads the changes below.
+    ctx.run_phase(Phase::AfterUpdate);
+
+    let root = ctx.entities().create();
+    let child = ctx.entities().create();
+    let grandchild = ctx.entities().create();
+    let other = ctx.entities().create();
+
+    ctx.commit_entities();
+
+    {
+      let mut parents = ctx.components_mut::<Parent>();
+
+      parents.insert(child, Parent(root));
+      parents.insert(grandchild, Parent(child));
+    }
+
+    ctx.run_phase(Phase::AfterUpdate);
+
+    assert_eq!(ctx.components::<Children>().get(root).map(|list| list.to_vec()), Some(vec![child]));
+    assert_eq!(
+      ctx.components::<Children>().get(child).map(|list| list.to_vec()),
+      Some(vec![grandchild])
+    );
+
+    assert!(ctx.entities().delete(root));
+    ctx.commit_entities();
+    ctx.run_phase(Phase::AfterUpdate);
+    ctx.commit_entities();
+
+    assert!(!ctx.entities().is_alive(child));
+    assert!(!ctx.entities().is_alive(grandchild));
+    assert!(ctx.entities().is_alive(other));
+  }
+}
@@ -2,11 +2,23 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+pub mod events;
+
+mod changes;
+mod commands;
 mod components;
 mod context;
 mod entities;
+mod hierarchy;
+mod query;
+mod snapshot;
+mod systems;
+mod transforms;
 
-pub use self::{components::*, context::*, entities::*};
+pub use self::{
+  changes::*, commands::*, components::*, context::*, entities::*, hierarchy::*, query::*,
+  snapshot::*, systems::*, transforms::*,
+};
 pub use shred::{ReadExpect as Resource, Resource as ResourceLike, WriteExpect as ResourceMut};
 pub use specs::storage;
 
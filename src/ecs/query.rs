@@ -0,0 +1,190 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use std::ops::Not;
+
+/// A join filter that yields `Some` with the value of the inner join for
+/// entities that match it and `None` for all other entities, also created
+/// with `Join::maybe()`.
+///
+/// A `MaybeJoin` matches every index, so it should be joined with at least one
+/// other join that is not a `MaybeJoin`, such as `&Entities`. It cannot be
+/// used in a `ParJoin`.
+pub use specs::join::MaybeJoin;
+
+/// A join filter that matches entities without a component, created by
+/// negating a `&Components<C>` or `&ComponentsMut<C>` with the `!` operator.
+pub struct Without<'a>(&'a BitSet);
+
+impl<'a> Join for Without<'a> {
+  type Type = ();
+  type Value = ();
+  type Mask = BitSetNot<&'a BitSet>;
+
+  unsafe fn open(self) -> (Self::Mask, Self::Value) {
+    (BitSetNot(self.0), ())
+  }
+
+  unsafe fn get(_: &mut Self::Value, _: specs::world::Index) -> Self::Type {}
+
+  fn is_unconstrained() -> bool {
+    true
+  }
+}
+
+unsafe impl<'a> ParJoin for Without<'a> {}
+
+impl<'a, 'b, C: Component> Not for &'a Components<'b, C> {
+  type Output = Without<'a>;
+
+  fn not(self) -> Without<'a> {
+    Without(self.mask())
+  }
+}
+
+impl<'a, 'b, C: Component> Not for &'a ComponentsMut<'b, C> {
+  type Output = Without<'a>;
+
+  fn not(self) -> Without<'a> {
+    Without(self.mask())
+  }
+}
+
+/// A join filter that matches entities matching either of two joins, yielding
+/// an `Option` with the value of each.
+///
+/// Both joins must have masks that can be cloned, such as the masks of
+/// `&Components<C>` and `&ComponentsMut<C>`. To match more than two joins,
+/// nest `Or` filters.
+pub struct Or<A, B>(pub A, pub B);
+
+impl<A, B> Join for Or<A, B>
+where
+  A: Join,
+  B: Join,
+  A::Mask: Clone,
+  B::Mask: Clone,
+{
+  type Type = (Option<A::Type>, Option<B::Type>);
+  type Value = ((A::Mask, A::Value), (B::Mask, B::Value));
+  type Mask = BitSetOr<A::Mask, B::Mask>;
+
+  unsafe fn open(self) -> (Self::Mask, Self::Value) {
+    let (a_mask, a_value) = self.0.open();
+    let (b_mask, b_value) = self.1.open();
+
+    (BitSetOr(a_mask.clone(), b_mask.clone()), ((a_mask, a_value), (b_mask, b_value)))
+  }
+
+  unsafe fn get((a, b): &mut Self::Value, index: specs::world::Index) -> Self::Type {
+    let a = if a.0.contains(index) { Some(A::get(&mut a.1, index)) } else { None };
+    let b = if b.0.contains(index) { Some(B::get(&mut b.1, index)) } else { None };
+
+    (a, b)
+  }
+
+  fn is_unconstrained() -> bool {
+    A::is_unconstrained() || B::is_unconstrained()
+  }
+}
+
+unsafe impl<A, B> ParJoin for Or<A, B>
+where
+  A: ParJoin,
+  B: ParJoin,
+  A::Mask: Clone,
+  B::Mask: Clone,
+{
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rayon::iter::ParallelIterator as _;
+  use specs::storage::DenseVecStorage;
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct A(u32);
+
+  impl Component for A {
+    type Storage = DenseVecStorage<Self>;
+  }
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct B(u32);
+
+  impl Component for B {
+    type Storage = DenseVecStorage<Self>;
+  }
+
+  /// Returns a context with four entities: one with `A`, one with `B`, one
+  /// with both, and one with neither.
+  fn context() -> (Context, [Entity; 4]) {
+    let mut ctx = Context::new();
+
+    ctx.register_component::<A>();
+    ctx.register_component::<B>();
+
+    let entities = [(); 4].map(|_| ctx.entities().create());
+
+    ctx.commit_entities();
+    ctx.components_mut().insert(entities[0], A(0));
+    ctx.components_mut().insert(entities[1], B(1));
+    ctx.components_mut().insert(entities[2], A(2));
+    ctx.components_mut().insert(entities[2], B(2));
+
+    (ctx, entities)
+  }
+
+  #[test]
+  fn without_matches_entities_missing_a_component() {
+    let (ctx, [_, with_b, _, neither]) = context();
+    let entities = ctx.entities();
+    let a = ctx.components::<A>();
+    let b = ctx.components_mut::<B>();
+
+    let joined: Vec<_> = (&entities, !&a).join().map(|(entity, ())| entity).collect();
+    let mut par_joined: Vec<_> = (&entities, !&a).par_join().map(|(entity, ())| entity).collect();
+
+    par_joined.sort();
+
+    assert_eq!(joined, [with_b, neither]);
+    assert_eq!(par_joined, joined);
+    assert_eq!((&b, !&a).join().map(|(b, ())| *b).collect::<Vec<_>>(), [B(1)]);
+  }
+
+  #[test]
+  fn or_matches_entities_with_either_component() {
+    let (ctx, [with_a, with_b, both, _]) = context();
+    let a = ctx.components::<A>();
+    let b = ctx.components::<B>();
+    let entities = ctx.entities();
+
+    let expected =
+      [(with_a, Some(A(0)), None), (with_b, None, Some(B(1))), (both, Some(A(2)), Some(B(2)))];
+
+    let joined: Vec<_> =
+      (&entities, Or(&a, &b)).join().map(|(e, (a, b))| (e, a.cloned(), b.cloned())).collect();
+
+    let mut par_joined: Vec<_> =
+      (&entities, Or(&a, &b)).par_join().map(|(e, (a, b))| (e, a.cloned(), b.cloned())).collect();
+
+    par_joined.sort_by_key(|(entity, ..)| *entity);
+
+    assert_eq!(joined, expected);
+    assert_eq!(par_joined, expected);
+  }
+
+  #[test]
+  fn maybe_yields_none_for_entities_missing_a_component() {
+    let (ctx, [with_a, with_b, both, neither]) = context();
+    let a = ctx.components::<A>();
+    let entities = ctx.entities();
+
+    let joined: Vec<_> = (&entities, (&a).maybe()).join().map(|(e, a)| (e, a.cloned())).collect();
+
+    assert_eq!(joined, [(with_a, Some(A(0))), (with_b, None), (both, Some(A(2))), (neither, None)]);
+  }
+}
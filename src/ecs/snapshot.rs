@@ -0,0 +1,577 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::*;
+use specs::Builder as _;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+thread_local! {
+  /// Indices of the entities in the snapshot being saved on this thread.
+  static SAVING: RefCell<Option<HashMap<Entity, usize>>> = const { RefCell::new(None) };
+  /// Entities created for the snapshot being loaded on this thread.
+  static LOADING: RefCell<Option<Vec<Entity>>> = const { RefCell::new(None) };
+}
+
+/// A component or resource type that can be saved in a `Snapshot`.
+///
+/// Fields containing entities must be serialized with the `ecs::entity_ref`
+/// module so that references are remapped to the restored entities on load.
+pub trait Persistent: Serialize + DeserializeOwned + Send + Sync + 'static {
+  /// Unique name of the type in snapshots.
+  const NAME: &'static str;
+}
+
+/// A saved copy of the entities, persistent components, and persistent
+/// resources in an ECS context.
+///
+/// Snapshots can be written in any serde format, including text formats such
+/// as TOML with `to_toml()` and binary formats such as bincode.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+  /// Values of persistent resources by name.
+  #[serde(default)]
+  pub resources: BTreeMap<String, SnapshotValue>,
+  /// Saved entities. Entity references in components are indices into this
+  /// list.
+  #[serde(default)]
+  pub entities: Vec<SnapshotEntity>,
+}
+
+impl Snapshot {
+  /// Parses a snapshot from a string containing TOML.
+  pub fn from_toml(source: &str) -> Result<Self, toml::de::Error> {
+    toml::from_str(source)
+  }
+
+  /// Serializes the snapshot to a string containing TOML.
+  pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+    toml::to_string(self)
+  }
+}
+
+/// A saved entity in a `Snapshot`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotEntity {
+  /// Values of the persistent components of the entity by name.
+  #[serde(default)]
+  pub components: BTreeMap<String, SnapshotValue>,
+}
+
+/// The saved value of a persistent component or resource in a `Snapshot`.
+///
+/// Values are written as plain values in human-readable formats. Binary
+/// formats cannot describe the structure of a value, so the kind of each value
+/// is written before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotValue(pub toml::Value);
+
+impl Serialize for SnapshotValue {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+      self.0.serialize(serializer)
+    } else {
+      TaggedValue::from(&self.0).serialize(serializer)
+    }
+  }
+}
+
+impl<'de> serde::Deserialize<'de> for SnapshotValue {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    if deserializer.is_human_readable() {
+      Ok(SnapshotValue(serde::Deserialize::deserialize(deserializer)?))
+    } else {
+      let value: TaggedValue = serde::Deserialize::deserialize(deserializer)?;
+
+      value.into_toml().map(SnapshotValue).map_err(serde::de::Error::custom)
+    }
+  }
+}
+
+/// A `toml::Value` with the kind of each value written explicitly.
+#[derive(Serialize, Deserialize)]
+enum TaggedValue {
+  String(String),
+  Integer(i64),
+  Float(f64),
+  Boolean(bool),
+  Datetime(String),
+  Array(Vec<TaggedValue>),
+  Table(BTreeMap<String, TaggedValue>),
+}
+
+impl TaggedValue {
+  /// Converts the value back to a `toml::Value`.
+  fn into_toml(self) -> Result<toml::Value, toml::value::DatetimeParseError> {
+    Ok(match self {
+      TaggedValue::String(value) => toml::Value::String(value),
+      TaggedValue::Integer(value) => toml::Value::Integer(value),
+      TaggedValue::Float(value) => toml::Value::Float(value),
+      TaggedValue::Boolean(value) => toml::Value::Boolean(value),
+      TaggedValue::Datetime(value) => toml::Value::Datetime(value.parse()?),
+
+      TaggedValue::Array(values) => {
+        toml::Value::Array(values.into_iter().map(Self::into_toml).collect::<Result<_, _>>()?)
+      }
+
+      TaggedValue::Table(values) => toml::Value::Table(
+        values
+          .into_iter()
+          .map(|(key, value)| Ok((key, value.into_toml()?)))
+          .collect::<Result<_, _>>()?,
+      ),
+    })
+  }
+}
+
+impl From<&toml::Value> for TaggedValue {
+  fn from(value: &toml::Value) -> Self {
+    match value {
+      toml::Value::String(value) => TaggedValue::String(value.clone()),
+      toml::Value::Integer(value) => TaggedValue::Integer(*value),
+      toml::Value::Float(value) => TaggedValue::Float(*value),
+      toml::Value::Boolean(value) => TaggedValue::Boolean(*value),
+      toml::Value::Datetime(value) => TaggedValue::Datetime(value.to_string()),
+      toml::Value::Array(values) => TaggedValue::Array(values.iter().map(Self::from).collect()),
+
+      toml::Value::Table(values) => TaggedValue::Table(
+        values.iter().map(|(key, value)| (key.clone(), Self::from(value))).collect(),
+      ),
+    }
+  }
+}
+
+/// A function that saves the values of a persistent type to a snapshot.
+type SaveFn =
+  fn(&specs::World, &HashMap<Entity, usize>, &mut Snapshot) -> Result<(), SnapshotError>;
+
+/// A deserialized value of a persistent type waiting to be added to the world.
+type Staged = Box<dyn FnOnce(&mut specs::World)>;
+
+/// A function that deserializes a value of a persistent type, for an entity if
+/// the type is a component type.
+type LoadFn = fn(&str, &toml::Value, Option<Entity>) -> Result<Staged, SnapshotError>;
+
+/// Functions for saving and loading a registered persistent type.
+pub(super) struct PersistentType {
+  name: &'static str,
+  save: SaveFn,
+  load: LoadFn,
+  is_component: bool,
+}
+
+impl PersistentType {
+  /// Returns a `PersistentType` for a component.
+  pub fn component<C: Component + Persistent>() -> Self {
+    PersistentType {
+      name: C::NAME,
+      save: save_component::<C>,
+      load: load_component::<C>,
+      is_component: true,
+    }
+  }
+
+  /// Returns a `PersistentType` for a resource.
+  pub fn resource<R: ResourceLike + Persistent>() -> Self {
+    PersistentType {
+      name: R::NAME,
+      save: save_resource::<R>,
+      load: load_resource::<R>,
+      is_component: false,
+    }
+  }
+
+  /// Returns the name of the type in snapshots.
+  pub fn name(&self) -> &'static str {
+    self.name
+  }
+
+  /// Returns `true` if the type is a component type.
+  pub fn is_component(&self) -> bool {
+    self.is_component
+  }
+}
+
+/// Saves all entities and the values of the given persistent types in `world`
+/// to a new snapshot.
+pub(super) fn save(
+  world: &specs::World,
+  types: &[PersistentType],
+) -> Result<Snapshot, SnapshotError> {
+  let entities = world.entities();
+  let indices: HashMap<_, _> =
+    (&entities).join().enumerate().map(|(i, entity)| (entity, i)).collect();
+
+  let mut snapshot = Snapshot {
+    resources: BTreeMap::new(),
+    entities: vec![SnapshotEntity::default(); indices.len()],
+  };
+
+  SAVING.with(|saving| *saving.borrow_mut() = Some(indices));
+
+  let _guard = ClearOnDrop;
+
+  SAVING.with(|saving| {
+    let saving = saving.borrow();
+    let indices = saving.as_ref().expect("snapshot entity indices not set");
+
+    for ty in types {
+      (ty.save)(world, indices, &mut snapshot)?;
+    }
+
+    Ok(snapshot)
+  })
+}
+
+/// Replaces all entities in `world` with new entities created from the given
+/// snapshot and loads the values of persistent types.
+///
+/// Returns the created entities in the same order as in the snapshot.
+pub(super) fn load(
+  world: &mut specs::World,
+  types: &[PersistentType],
+  snapshot: &Snapshot,
+) -> Result<Vec<Entity>, SnapshotError> {
+  let types_by_name: HashMap<_, _> =
+    types.iter().map(|ty| ((ty.name, ty.is_component), ty)).collect();
+
+  // Check for unknown types before changing anything.
+  for name in snapshot.resources.keys() {
+    if !types_by_name.contains_key(&(name.as_str(), false)) {
+      return Err(SnapshotError::UnknownType(name.clone()));
+    }
+  }
+
+  for name in snapshot.entities.iter().flat_map(|entity| entity.components.keys()) {
+    if !types_by_name.contains_key(&(name.as_str(), true)) {
+      return Err(SnapshotError::UnknownType(name.clone()));
+    }
+  }
+
+  // Create the new entities alongside the existing ones so that entity
+  // references can be remapped while deserializing.
+  let created = snapshot.entities.iter().map(|_| world.create_entity().build()).collect::<Vec<_>>();
+
+  LOADING.with(|loading| *loading.borrow_mut() = Some(created.clone()));
+
+  let _guard = ClearOnDrop;
+
+  // Deserialize every value before changing anything else so that a failure
+  // leaves the world as it was.
+  let staged = (|| {
+    let mut staged = Vec::new();
+
+    for (name, value) in &snapshot.resources {
+      staged.push((types_by_name[&(name.as_str(), false)].load)(name, &value.0, None)?);
+    }
+
+    for (entity, saved) in created.iter().zip(&snapshot.entities) {
+      for (name, value) in &saved.components {
+        staged.push((types_by_name[&(name.as_str(), true)].load)(name, &value.0, Some(*entity))?);
+      }
+    }
+
+    Ok(staged)
+  })();
+
+  let staged = match staged {
+    Ok(staged) => staged,
+
+    Err(err) => {
+      let _ = world.delete_entities(&created);
+
+      world.maintain();
+
+      return Err(err);
+    }
+  };
+
+  // Replace the existing entities with the new ones.
+  {
+    let entities = world.entities();
+    let created: HashSet<_> = created.iter().cloned().collect();
+
+    for entity in (&entities).join().filter(|entity| !created.contains(entity)) {
+      let _ = entities.delete(entity);
+    }
+  }
+
+  world.maintain();
+
+  for add in staged {
+    add(world);
+  }
+
+  Ok(created)
+}
+
+/// Saves all components of type `C` to the snapshot.
+fn save_component<C: Component + Persistent>(
+  world: &specs::World,
+  indices: &HashMap<Entity, usize>,
+  snapshot: &mut Snapshot,
+) -> Result<(), SnapshotError> {
+  let entities = world.entities();
+  let components = world.read_storage::<C>();
+
+  for (entity, component) in (&entities, &components).join() {
+    let value = toml::Value::try_from(component).map_err(SnapshotError::serialize(C::NAME))?;
+
+    snapshot.entities[indices[&entity]].components.insert(C::NAME.to_owned(), SnapshotValue(value));
+  }
+
+  Ok(())
+}
+
+/// Deserializes a component of type `C` for the given entity.
+fn load_component<C: Component + Persistent>(
+  name: &str,
+  value: &toml::Value,
+  entity: Option<Entity>,
+) -> Result<Staged, SnapshotError> {
+  let component: C = value.clone().try_into().map_err(SnapshotError::deserialize(name))?;
+  let entity = entity.expect("component loaded without an entity");
+
+  Ok(Box::new(move |world| {
+    let _ = world.write_storage::<C>().insert(entity, component);
+  }))
+}
+
+/// Saves the resource of type `R` to the snapshot if it exists.
+fn save_resource<R: ResourceLike + Persistent>(
+  world: &specs::World,
+  _: &HashMap<Entity, usize>,
+  snapshot: &mut Snapshot,
+) -> Result<(), SnapshotError> {
+  if !world.res.has_value::<R>() {
+    return Ok(());
+  }
+
+  let resource = world.read_resource::<R>();
+  let value = toml::Value::try_from(&*resource).map_err(SnapshotError::serialize(R::NAME))?;
+
+  snapshot.resources.insert(R::NAME.to_owned(), SnapshotValue(value));
+
+  Ok(())
+}
+
+/// Deserializes the resource of type `R`, which replaces any existing value
+/// when added.
+fn load_resource<R: ResourceLike + Persistent>(
+  name: &str,
+  value: &toml::Value,
+  _: Option<Entity>,
+) -> Result<Staged, SnapshotError> {
+  let resource: R = value.clone().try_into().map_err(SnapshotError::deserialize(name))?;
+
+  Ok(Box::new(move |world| world.add_resource(resource)))
+}
+
+/// Clears the thread-local entity maps when dropped.
+struct ClearOnDrop;
+
+impl Drop for ClearOnDrop {
+  fn drop(&mut self) {
+    SAVING.with(|saving| saving.borrow_mut().take());
+    LOADING.with(|loading| loading.borrow_mut().take());
+  }
+}
+
+/// An error that occurred while saving or loading a snapshot.
+#[derive(Debug)]
+pub enum SnapshotError {
+  /// A value of the named type could not be serialized.
+  SerializeFailed(String, toml::ser::Error),
+  /// A value of the named type could not be deserialized.
+  DeserializeFailed(String, toml::de::Error),
+  /// The snapshot contains a type that is not registered as persistent.
+  UnknownType(String),
+}
+
+impl SnapshotError {
+  fn serialize(name: &str) -> impl FnOnce(toml::ser::Error) -> Self + '_ {
+    move |cause| SnapshotError::SerializeFailed(name.to_owned(), cause)
+  }
+
+  fn deserialize(name: &str) -> impl FnOnce(toml::de::Error) -> Self + '_ {
+    move |cause| SnapshotError::DeserializeFailed(name.to_owned(), cause)
+  }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl fmt::Display for SnapshotError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SnapshotError::SerializeFailed(name, cause) => {
+        write!(f, "failed to serialize {:?}: {}", name, cause)
+      }
+
+      SnapshotError::DeserializeFailed(name, cause) => {
+        write!(f, "failed to deserialize {:?}: {}", name, cause)
+      }
+
+      SnapshotError::UnknownType(name) => write!(f, "unknown persistent type {:?}", name),
+    }
+  }
+}
+
+/// Serde functions for `Entity` fields of persistent types.
+///
+/// Use `#[serde(with = "nova::ecs::entity_ref")]` on `Entity` fields, or the
+/// `option` and `vec` submodules for `Option<Entity>` and `Vec<Entity>` fields.
+/// Entities are saved as indices into the snapshot's entity list and remapped
+/// to the restored entities when loaded. Entity references cannot be
+/// serialized outside of snapshots, and references to entities that are not
+/// alive cannot be saved.
+pub mod entity_ref {
+  use super::{Entity, LOADING, SAVING};
+  use serde::de::{Deserialize, Deserializer, Error as _};
+  use serde::ser::{Error as _, Serialize, Serializer};
+
+  pub fn serialize<S: Serializer>(entity: &Entity, serializer: S) -> Result<S::Ok, S::Error> {
+    let index = SAVING.with(|saving| match saving.borrow().as_ref() {
+      Some(indices) => indices.get(entity).cloned().ok_or("entity reference is not alive"),
+      None => Err("entity references can only be serialized in snapshots"),
+    });
+
+    (index.map_err(S::Error::custom)? as u64).serialize(serializer)
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Entity, D::Error> {
+    let index = u64::deserialize(deserializer)?;
+
+    LOADING.with(|loading| match loading.borrow().as_ref() {
+      Some(entities) => entities.get(index as usize).cloned().ok_or_else(|| {
+        D::Error::custom(format_args!("entity reference {} is not in the snapshot", index))
+      }),
+
+      None => Err(D::Error::custom("entity references can only be deserialized in snapshots")),
+    })
+  }
+
+  /// Serde functions for `Option<Entity>` fields.
+  ///
+  /// Fields using this module should also have `#[serde(default)]` because
+  /// formats such as TOML omit `None` values.
+  pub mod option {
+    use super::*;
+
+    #[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+    struct EntityRef(#[serde(with = "super")] Entity);
+
+    pub fn serialize<S: Serializer>(
+      entity: &Option<Entity>,
+      serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+      entity.map(EntityRef).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+      deserializer: D,
+    ) -> Result<Option<Entity>, D::Error> {
+      Ok(Option::<EntityRef>::deserialize(deserializer)?.map(|entity| entity.0))
+    }
+  }
+
+  /// Serde functions for `Vec<Entity>` fields.
+  pub mod vec {
+    use super::*;
+
+    #[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+    struct EntityRef(#[serde(with = "super")] Entity);
+
+    pub fn serialize<S: Serializer>(entities: &[Entity], serializer: S) -> Result<S::Ok, S::Error> {
+      serializer.collect_seq(entities.iter().cloned().map(EntityRef))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+      deserializer: D,
+    ) -> Result<Vec<Entity>, D::Error> {
+      Ok(Vec::<EntityRef>::deserialize(deserializer)?.into_iter().map(|entity| entity.0).collect())
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ecs::storage::DenseVecStorage;
+
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+  struct Score(u32);
+
+  impl Component for Score {
+    type Storage = DenseVecStorage<Self>;
+  }
+
+  impl Persistent for Score {
+    const NAME: &'static str = "score";
+  }
+
+  fn context() -> Context {
+    let mut ctx = Context::new();
+
+    ctx.register_persistent_component::<Parent>();
+    ctx.register_persistent_component::<Score>();
+
+    ctx
+  }
+
+  #[test]
+  fn snapshots_round_trip_through_binary_formats() {
+    let mut ctx = context();
+    let parent = ctx.entities().create();
+    let child = ctx.entities().create();
+
+    ctx.commit_entities();
+    ctx.components_mut::<Score>().insert(parent, Score(7));
+    ctx.components_mut::<Parent>().insert(child, Parent(parent));
+
+    let snapshot = ctx.save_snapshot().unwrap();
+    let bytes = bincode::serialize(&snapshot).unwrap();
+    let loaded: Snapshot = bincode::deserialize(&bytes).unwrap();
+
+    assert_eq!(loaded, snapshot);
+
+    let mut restored = context();
+    let entities = restored.load_snapshot(&loaded).unwrap();
+
+    assert_eq!(restored.components::<Score>().get(entities[0]), Some(&Score(7)));
+    assert_eq!(restored.components::<Parent>().get(entities[1]), Some(&Parent(entities[0])));
+  }
+
+  #[test]
+  fn failed_load_leaves_world_unchanged() {
+    let mut ctx = context();
+    let entity = ctx.entities().create();
+
+    ctx.commit_entities();
+    ctx.components_mut::<Score>().insert(entity, Score(1));
+
+    let snapshot = Snapshot::from_toml(
+      r#"
+      [[entities]]
+      components = { score = 2 }
+
+      [[entities]]
+      components = { score = "invalid" }
+      "#,
+    )
+    .unwrap();
+
+    match ctx.load_snapshot(&snapshot) {
+      Err(SnapshotError::DeserializeFailed(name, _)) => assert_eq!(name, "score"),
+      result => panic!("unexpected result {:?}", result),
+    }
+
+    assert!(ctx.entities().is_alive(entity));
+    assert_eq!(ctx.components::<Score>().get(entity), Some(&Score(1)));
+    assert_eq!((&ctx.components::<Score>()).join().count(), 1);
+  }
+}
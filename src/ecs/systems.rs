@@ -0,0 +1,221 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+pub use specs::{RunNow, System, SystemData};
+
+use specs::{Dispatcher, DispatcherBuilder};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// One of the phases in which scheduled systems run.
+///
+/// Phases always run in the order they are declared in, from `BeforeUpdate`
+/// to `AfterUpdate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+  /// Runs before game state is updated, such as to process input.
+  ///
+  /// The app main loop runs this phase once per frame.
+  BeforeUpdate,
+  /// Runs to update game state.
+  ///
+  /// The app main loop runs this phase once per fixed step of the clock, which
+  /// may be zero or more times per frame.
+  Update,
+  /// Runs after game state is updated, such as to lay out UI or prepare data
+  /// for rendering.
+  ///
+  /// The app main loop runs this phase once per frame.
+  AfterUpdate,
+}
+
+impl Phase {
+  /// All phases in the order in which they run.
+  pub const ALL: [Phase; 3] = [Phase::BeforeUpdate, Phase::Update, Phase::AfterUpdate];
+}
+
+/// Systems scheduled to run in each `Phase`.
+pub(super) struct Schedule {
+  phases: [PhaseSystems; 3],
+  thread_pool: Arc<rayon::ThreadPool>,
+}
+
+impl Default for Schedule {
+  fn default() -> Self {
+    let thread_pool =
+      rayon::ThreadPoolBuilder::new().build().expect("could not create ECS thread pool");
+
+    Self { phases: Default::default(), thread_pool: Arc::new(thread_pool) }
+  }
+}
+
+impl Schedule {
+  /// Adds a system that may run in parallel with others in the given phase.
+  ///
+  /// Panics if `name` is already used in the phase or if any of the
+  /// `dependencies` are not the name of a system in the phase.
+  pub fn add<S>(&mut self, phase: Phase, name: &str, dependencies: &[&str], system: S)
+  where
+    S: for<'a> System<'a> + Send + 'static,
+  {
+    let thread_pool = &self.thread_pool;
+    let systems = &mut self.phases[phase as usize];
+
+    // Only pass dependencies in the pending batch to the builder. Systems in
+    // earlier batches already run first.
+    let mut pending_dependencies = Vec::with_capacity(dependencies.len());
+
+    for dependency in dependencies {
+      if !systems.names.contains(*dependency) {
+        panic!("system {:?} depends on unknown system {:?} in {:?}", name, dependency, phase);
+      }
+
+      if systems.pending_names.contains(*dependency) {
+        pending_dependencies.push(*dependency);
+      }
+    }
+
+    if !name.is_empty() {
+      if !systems.names.insert(name.to_owned()) {
+        panic!("a system named {:?} is already scheduled in {:?}", name, phase);
+      }
+
+      systems.pending_names.insert(name.to_owned());
+    }
+
+    systems
+      .pending
+      .get_or_insert_with(|| DispatcherBuilder::new().with_pool(thread_pool.clone()))
+      .add(system, name, &pending_dependencies);
+  }
+
+  /// Adds a system that runs on the thread running the given phase after all
+  /// other systems in its batch.
+  pub fn add_thread_local<S>(&mut self, phase: Phase, system: S)
+  where
+    S: for<'a> System<'a> + 'static,
+  {
+    let thread_pool = &self.thread_pool;
+
+    self.phases[phase as usize]
+      .pending
+      .get_or_insert_with(|| DispatcherBuilder::new().with_pool(thread_pool.clone()))
+      .add_thread_local(system);
+  }
+
+  /// Runs all systems in the given phase.
+  pub fn run(&mut self, phase: Phase, res: &mut specs::Resources) {
+    let systems = &mut self.phases[phase as usize];
+
+    // Build any systems added since the last run into a new dispatcher and
+    // let them set up their resources.
+    if let Some(builder) = systems.pending.take() {
+      let mut dispatcher = builder.build();
+
+      dispatcher.setup(res);
+
+      systems.dispatchers.push(dispatcher);
+      systems.pending_names.clear();
+    }
+
+    for dispatcher in &mut systems.dispatchers {
+      dispatcher.dispatch(res);
+    }
+  }
+}
+
+/// Systems scheduled in a single `Phase`.
+///
+/// Systems are built into dispatchers in batches the first time the phase runs
+/// after they are added. Batches run in the order they were built in.
+#[derive(Default)]
+struct PhaseSystems {
+  dispatchers: Vec<Dispatcher<'static, 'static>>,
+  pending: Option<DispatcherBuilder<'static, 'static>>,
+  pending_names: HashSet<String>,
+  names: HashSet<String>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ecs::Context;
+
+  /// A resource listing the systems that have run in order.
+  #[derive(Default)]
+  struct Ran(Vec<&'static str>);
+
+  /// A system that adds its name to `Ran`.
+  struct Record(&'static str);
+
+  impl<'a> System<'a> for Record {
+    type SystemData = specs::Write<'a, Ran>;
+
+    fn run(&mut self, mut ran: Self::SystemData) {
+      ran.0.push(self.0);
+    }
+  }
+
+  /// Runs the given phase and returns the systems that ran in order.
+  fn run(ctx: &mut Context, phase: Phase) -> Vec<&'static str> {
+    ctx.run_phase(phase);
+
+    std::mem::take(&mut ctx.resource_mut::<Ran>().0)
+  }
+
+  #[test]
+  fn phases_run_in_order() {
+    let mut ctx = Context::new();
+
+    ctx.schedule(Phase::AfterUpdate, Record("after"));
+    ctx.schedule(Phase::Update, Record("update"));
+    ctx.schedule(Phase::BeforeUpdate, Record("before"));
+    ctx.run_phases();
+
+    assert_eq!(ctx.resource::<Ran>().0, ["before", "update", "after"]);
+  }
+
+  #[test]
+  fn systems_run_after_their_dependencies() {
+    let mut ctx = Context::new();
+
+    ctx.schedule_after(Phase::Update, "c", &[], Record("c"));
+    ctx.schedule_after(Phase::Update, "a", &["c"], Record("a"));
+    ctx.schedule_after(Phase::Update, "b", &["a", "c"], Record("b"));
+
+    assert_eq!(run(&mut ctx, Phase::Update), ["c", "a", "b"]);
+
+    // Systems scheduled after the phase has run depend on earlier batches
+    // without naming them.
+    ctx.schedule_after(Phase::Update, "d", &["b"], Record("d"));
+
+    assert_eq!(run(&mut ctx, Phase::Update), ["c", "a", "b", "d"]);
+  }
+
+  #[test]
+  fn thread_local_systems_run_after_all_parallel_systems_in_their_batch() {
+    let mut ctx = Context::new();
+
+    ctx.schedule(Phase::Update, Record("first"));
+    ctx.schedule_seq(Phase::Update, Record("seq"));
+    ctx.schedule(Phase::Update, Record("second"));
+
+    let ran = run(&mut ctx, Phase::Update);
+
+    assert_eq!(ran.len(), 3);
+    assert_eq!(ran.last(), Some(&"seq"));
+
+    ctx.schedule(Phase::Update, Record("later"));
+
+    assert_eq!(run(&mut ctx, Phase::Update)[2..], ["seq", "later"]);
+  }
+
+  #[test]
+  #[should_panic(expected = "depends on unknown system")]
+  fn unknown_dependencies_panic() {
+    let mut ctx = Context::new();
+
+    ctx.schedule_after(Phase::Update, "a", &["missing"], Record("a"));
+  }
+}
@@ -0,0 +1,366 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::storage::{ComponentEvent, DenseVecStorage, FlaggedStorage};
+use super::*;
+use crate::log;
+use crate::math;
+use specs::ReaderId;
+use std::ops::{Deref, DerefMut};
+
+/// Common trait for transformations that can be used in `Transform` and
+/// `GlobalTransform` components.
+///
+/// It is implemented for `math::Transform2<f32>` and `math::Transform3<f32>`.
+pub trait Transformation: Copy + Send + Sync + 'static {
+  /// Returns the transformation that leaves everything unchanged.
+  fn identity() -> Self;
+
+  /// Returns the transformation of a child with the `local` transformation
+  /// relative to a parent with this transformation.
+  fn then_local(&self, local: &Self) -> Self;
+}
+
+impl Transformation for math::Transform2<f32> {
+  fn identity() -> Self {
+    math::Transform2::identity()
+  }
+
+  fn then_local(&self, local: &Self) -> Self {
+    self * local
+  }
+}
+
+impl Transformation for math::Transform3<f32> {
+  fn identity() -> Self {
+    math::Transform3::identity()
+  }
+
+  fn then_local(&self, local: &Self) -> Self {
+    self * local
+  }
+}
+
+/// A component containing the transformation of an entity relative to its
+/// parent, or to the world if it has no parent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform<T>(pub T);
+
+impl<T: Transformation> Default for Transform<T> {
+  fn default() -> Self {
+    Transform(T::identity())
+  }
+}
+
+impl<T: Transformation> Component for Transform<T> {
+  type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+impl<T> Deref for Transform<T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    &self.0
+  }
+}
+
+impl<T> DerefMut for Transform<T> {
+  fn deref_mut(&mut self) -> &mut T {
+    &mut self.0
+  }
+}
+
+/// A component containing the transformation of an entity relative to the
+/// world.
+///
+/// This component is maintained by the `PropagateTransforms<T>` system for
+/// each entity with a `Transform<T>` component and should not be modified
+/// directly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlobalTransform<T>(T);
+
+impl<T: Transformation> Component for GlobalTransform<T> {
+  type Storage = DenseVecStorage<Self>;
+}
+
+impl<T> Deref for GlobalTransform<T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    &self.0
+  }
+}
+
+/// A system that computes the `GlobalTransform<T>` components of entities
+/// with changed `Transform<T>` or `Parent` components and of all of their
+/// descendants.
+///
+/// Entities in the hierarchy without a `Transform<T>` component are treated
+/// as if they had an identity transformation. Entities in a cycle of `Parent`
+/// components are skipped until `MaintainHierarchy` breaks the cycle.
+pub struct PropagateTransforms<T> {
+  transform_reader: Option<ReaderId<ComponentEvent>>,
+  parent_reader: Option<ReaderId<ComponentEvent>>,
+  changed: BitSet,
+  visited: BitSet,
+  stack: Vec<(Entity, T)>,
+}
+
+impl<T> Default for PropagateTransforms<T> {
+  fn default() -> Self {
+    Self {
+      transform_reader: None,
+      parent_reader: None,
+      changed: BitSet::new(),
+      visited: BitSet::new(),
+      stack: Vec::new(),
+    }
+  }
+}
+
+impl<T: Transformation> PropagateTransforms<T> {
+  /// Returns the name the system is scheduled with by `set_up_transforms()`.
+  pub fn name() -> &'static str {
+    std::any::type_name::<Self>()
+  }
+}
+
+impl<'a, T: Transformation> System<'a> for PropagateTransforms<T> {
+  type SystemData = (
+    specs::Entities<'a>,
+    specs::ReadStorage<'a, Parent>,
+    specs::ReadStorage<'a, Children>,
+    specs::ReadStorage<'a, Transform<T>>,
+    specs::WriteStorage<'a, GlobalTransform<T>>,
+  );
+
+  fn setup(&mut self, res: &mut specs::Resources) {
+    Self::SystemData::setup(res);
+
+    self.transform_reader = Some(specs::WriteStorage::<Transform<T>>::fetch(res).register_reader());
+
+    self.parent_reader = Some(specs::WriteStorage::<Parent>::fetch(res).register_reader());
+  }
+
+  fn run(&mut self, (entities, parents, children, transforms, mut globals): Self::SystemData) {
+    let transform_reader = self.transform_reader.as_mut().expect("system was not set up");
+    let parent_reader = self.parent_reader.as_mut().expect("system was not set up");
+
+    self.changed.clear();
+
+    let events =
+      transforms.channel().read(transform_reader).chain(parents.channel().read(parent_reader));
+
+    for event in events {
+      match event {
+        ComponentEvent::Inserted(id)
+        | ComponentEvent::Modified(id)
+        | ComponentEvent::Removed(id) => {
+          self.changed.add(*id);
+        }
+      }
+    }
+
+    for id in (&self.changed).join() {
+      let entity = entities.entity(id);
+
+      if !entities.is_alive(entity) {
+        continue;
+      }
+
+      // Find the nearest ancestor with a global transform. If an ancestor has
+      // changed, this entity is updated along with it instead.
+      let mut parent_global = None;
+      let mut ancestor = parents.get(entity);
+      let mut cyclic = false;
+
+      self.visited.clear();
+      self.visited.add(id);
+
+      while let Some(Parent(parent)) = ancestor {
+        // `BitSet::add` returns `true` if the entity was already visited.
+        if self.visited.add(parent.id()) {
+          cyclic = true;
+          break;
+        }
+
+        if self.changed.contains(parent.id()) {
+          break;
+        }
+
+        if let Some(global) = globals.get(*parent) {
+          parent_global = Some(global.0);
+          break;
+        }
+
+        ancestor = parents.get(*parent);
+      }
+
+      if cyclic {
+        if let Some(logger) = log::try_logger() {
+          log::warn!(logger, "skipped transform of entity in parent cycle"; "entity" => log::Debug(entity));
+        }
+
+        continue;
+      }
+
+      if ancestor.is_some_and(|Parent(parent)| self.changed.contains(parent.id())) {
+        continue;
+      }
+
+      self.stack.push((entity, parent_global.unwrap_or_else(T::identity)));
+      self.visited.clear();
+
+      while let Some((entity, parent_global)) = self.stack.pop() {
+        if self.visited.add(entity.id()) {
+          continue;
+        }
+
+        let global = match transforms.get(entity) {
+          Some(local) => {
+            let global = parent_global.then_local(&local.0);
+            let _ = globals.insert(entity, GlobalTransform(global));
+            global
+          }
+
+          None => {
+            globals.remove(entity);
+            parent_global
+          }
+        };
+
+        if let Some(list) = children.get(entity) {
+          self.stack.extend(list.iter().map(|child| (*child, global)));
+        }
+      }
+    }
+  }
+}
+
+/// Registers the `Transform<T>` and `GlobalTransform<T>` components and
+/// schedules the `PropagateTransforms<T>` system in the `AfterUpdate` phase.
+///
+/// # Panics
+///
+/// Panics if `set_up_hierarchy()` has not been called first.
+pub fn set_up_transforms<T: Transformation>(ctx: &mut Context) {
+  ctx.register_component::<Transform<T>>();
+  ctx.register_component::<GlobalTransform<T>>();
+
+  ctx.schedule_after(
+    Phase::AfterUpdate,
+    PropagateTransforms::<T>::name(),
+    &[MaintainHierarchy::name()],
+    PropagateTransforms::<T>::default(),
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  type Transform2 = math::Transform2<f32>;
+
+  #[test]
+  fn entities_in_parent_cycles_are_skipped() {
+    let mut ctx = Context::new();
+
+    ctx.register_component::<Parent>();
+    ctx.register_component::<Children>();
+    ctx.register_component::<Transform<Transform2>>();
+    ctx.register_component::<GlobalTransform<Transform2>>();
+    ctx.schedule(Phase::AfterUpdate, PropagateTransforms::<Transform2>::default());
+
+    // Set up the system so that it reads the changes below.
+    ctx.run_phase(Phase::AfterUpdate);
+
+    let first = ctx.entities().create();
+    let second = ctx.entities().create();
+    let other = ctx.entities().create();
+
+    ctx.commit_entities();
+
+    {
+      let mut parents = ctx.components_mut::<Parent>();
+
+      parents.insert(first, Parent(second));
+      parents.insert(second, Parent(first));
+    }
+
+    {
+      let mut transforms = ctx.components_mut::<Transform<Transform2>>();
+
+      for entity in [first, second, other] {
+        transforms.insert(entity, Transform::default());
+      }
+    }
+
+    ctx.run_phase(Phase::AfterUpdate);
+
+    let globals = ctx.components::<GlobalTransform<Transform2>>();
+
+    assert!(globals.get(first).is_none());
+    assert!(globals.get(second).is_none());
+    assert_eq!(globals.get(other).map(|global| global.0), Some(Transform2::identity()));
+  }
+
+  fn translation(x: f32, y: f32) -> Transform2 {
+    Transform2::from_matrix_unchecked(nalgebra::Matrix3::new_translation(&nalgebra::Vector2::new(
+      x, y,
+    )))
+  }
+
+  #[test]
+  fn children_are_transformed_relative_to_their_parents() {
+    let mut ctx = Context::new();
+
+    set_up_hierarchy(&mut ctx);
+    set_up_transforms::<Transform2>(&mut ctx);
+
+    // Set up the systems so that they read the changes below.
+    ctx.run_phase(Phase::AfterUpdate);
+
+    let parent = ctx.entities().create();
+    let child = ctx.entities().create();
+
+    ctx.commit_entities();
+
+    let parent_local = translation(10.0, 0.0)
+      * Transform2::from_matrix_unchecked(nalgebra::Matrix3::new_scaling(2.0));
+    let child_local = translation(1.0, 2.0);
+
+    ctx.components_mut::<Parent>().insert(child, Parent(parent));
+
+    {
+      let mut transforms = ctx.components_mut::<Transform<Transform2>>();
+
+      transforms.insert(parent, Transform(parent_local));
+      transforms.insert(child, Transform(child_local));
+    }
+
+    ctx.run_phase(Phase::AfterUpdate);
+
+    {
+      let globals = ctx.components::<GlobalTransform<Transform2>>();
+      let child_global = globals.get(child).map(|global| global.0);
+
+      assert_eq!(child_global, Some(parent_local * child_local));
+      assert_eq!(
+        child_global.map(|global| global * nalgebra::Point2::origin()),
+        Some(nalgebra::Point2::new(12.0, 4.0))
+      );
+    }
+
+    // Moving the parent moves the child with it.
+    let moved = translation(-5.0, 3.0);
+
+    ctx.components_mut::<Transform<Transform2>>().get_mut(parent).unwrap().0 = moved;
+    ctx.run_phase(Phase::AfterUpdate);
+
+    let globals = ctx.components::<GlobalTransform<Transform2>>();
+
+    assert_eq!(globals.get(parent).map(|global| global.0), Some(moved));
+    assert_eq!(globals.get(child).map(|global| global.0), Some(moved * child_local));
+  }
+}
@@ -0,0 +1,460 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod names;
+mod parse;
+
+pub use self::parse::*;
+
+use super::*;
+
+/// A 32-bit RGBA color.
+///
+/// Components are stored as given without tracking a color space. Colors
+/// created from hex strings, CSS names, 8-bit values, HSV, or HSL are sRGB
+/// encoded, which is what the surfaces expect. Use [`Color::to_linear()`]
+/// before doing math that should happen in linear space and
+/// [`Color::to_srgb()`] to convert back.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+  /// The red component of the color.
+  pub r: f32,
+  /// The green component of the color.
+  pub g: f32,
+  /// The blue component of the color.
+  pub b: f32,
+  /// The alpha component of the color.
+  pub a: f32,
+}
+
+impl Color {
+  /// Black with completely transparent alpha.
+  pub const TRANSPARENT: Self = Color::new(0.0, 0.0, 0.0, 0.0);
+
+  /// Opaque white.
+  pub const WHITE: Self = Color::new(1.0, 1.0, 1.0, 1.0);
+
+  /// Opaque black.
+  pub const BLACK: Self = Color::new(0.0, 0.0, 0.0, 1.0);
+
+  /// Creates a new color with the given component values.
+  pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+    Color { r, g, b, a }
+  }
+
+  /// Creates a new opaque color with the given red, green, and blue values.
+  pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+    Color { r, g, b, a: 1.0 }
+  }
+
+  /// Creates a new color from 8-bit component values.
+  pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Self {
+    Color::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0)
+  }
+
+  /// Creates a new opaque color from a `0xRRGGBB` value.
+  pub fn from_rgb_u32(rgb: u32) -> Self {
+    Self::from_rgba_u32(rgb << 8 | 0xff)
+  }
+
+  /// Creates a new color from a `0xRRGGBBAA` value.
+  pub fn from_rgba_u32(rgba: u32) -> Self {
+    let [r, g, b, a] = rgba.to_be_bytes();
+
+    Self::from_rgba8(r, g, b, a)
+  }
+
+  /// Parses a color from a `#rgb`, `#rgba`, `#rrggbb`, or `#rrggbbaa` hex
+  /// string or a CSS color name.
+  ///
+  /// This is equivalent to `str::parse()`.
+  pub fn parse(s: &str) -> Result<Self, ParseColorError> {
+    s.parse()
+  }
+
+  /// Creates a new color from hue, saturation, value, and alpha.
+  ///
+  /// The hue is in degrees and wraps around. The other components are
+  /// clamped between 0 and 1.
+  pub fn from_hsva(hue: f32, saturation: f32, value: f32, alpha: f32) -> Self {
+    let saturation = math::clamp(saturation, 0.0..=1.0);
+    let value = math::clamp(value, 0.0..=1.0);
+    let chroma = value * saturation;
+
+    Self::from_hue_chroma(hue, chroma, value - chroma, alpha)
+  }
+
+  /// Creates a new color from hue, saturation, lightness, and alpha.
+  ///
+  /// The hue is in degrees and wraps around. The other components are
+  /// clamped between 0 and 1.
+  pub fn from_hsla(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> Self {
+    let saturation = math::clamp(saturation, 0.0..=1.0);
+    let lightness = math::clamp(lightness, 0.0..=1.0);
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+
+    Self::from_hue_chroma(hue, chroma, lightness - chroma / 2.0, alpha)
+  }
+
+  /// Creates a color from the shared parts of the HSV and HSL formulas.
+  fn from_hue_chroma(hue: f32, chroma: f32, min: f32, alpha: f32) -> Self {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match sector as u32 {
+      0 => (chroma, x, 0.0),
+      1 => (x, chroma, 0.0),
+      2 => (0.0, chroma, x),
+      3 => (0.0, x, chroma),
+      4 => (x, 0.0, chroma),
+      _ => (chroma, 0.0, x),
+    };
+
+    Color::new(r + min, g + min, b + min, math::clamp(alpha, 0.0..=1.0))
+  }
+
+  /// Returns the hue in degrees, saturation, and value of the color.
+  pub fn to_hsv(self) -> (f32, f32, f32) {
+    let (hue, min, max) = self.hue_min_max();
+    let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+
+    (hue, saturation, max)
+  }
+
+  /// Returns the hue in degrees, saturation, and lightness of the color.
+  pub fn to_hsl(self) -> (f32, f32, f32) {
+    let (hue, min, max) = self.hue_min_max();
+    let lightness = (max + min) / 2.0;
+
+    let saturation = if lightness > 0.0 && lightness < 1.0 {
+      (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+    } else {
+      0.0
+    };
+
+    (hue, saturation, lightness)
+  }
+
+  /// Returns the hue in degrees and the smallest and largest RGB components.
+  fn hue_min_max(self) -> (f32, f32, f32) {
+    let max = self.r.max(self.g).max(self.b);
+    let min = self.r.min(self.g).min(self.b);
+    let chroma = max - min;
+
+    let hue = if chroma == 0.0 {
+      0.0
+    } else if max == self.r {
+      60.0 * ((self.g - self.b) / chroma).rem_euclid(6.0)
+    } else if max == self.g {
+      60.0 * ((self.b - self.r) / chroma + 2.0)
+    } else {
+      60.0 * ((self.r - self.g) / chroma + 4.0)
+    };
+
+    (hue, min, max)
+  }
+
+  /// Returns the color with the given alpha value.
+  pub fn with_alpha(self, a: f32) -> Self {
+    Color { a, ..self }
+  }
+
+  /// Converts sRGB encoded components to linear components.
+  ///
+  /// The alpha component is unchanged.
+  pub fn to_linear(self) -> Self {
+    let convert = |c: f32| {
+      if c <= 0.04045 {
+        c / 12.92
+      } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+      }
+    };
+
+    Color::new(convert(self.r), convert(self.g), convert(self.b), self.a)
+  }
+
+  /// Converts linear components to sRGB encoded components.
+  ///
+  /// The alpha component is unchanged.
+  pub fn to_srgb(self) -> Self {
+    let convert = |c: f32| {
+      if c <= 0.003_130_8 {
+        c * 12.92
+      } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+      }
+    };
+
+    Color::new(convert(self.r), convert(self.g), convert(self.b), self.a)
+  }
+
+  /// Returns the color with its red, green, and blue components multiplied
+  /// by its alpha component.
+  pub fn premultiply(self) -> Self {
+    Color::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+  }
+
+  /// Returns the color with premultiplied alpha removed from its red, green,
+  /// and blue components.
+  ///
+  /// Fully transparent colors become [`Color::TRANSPARENT`].
+  pub fn unpremultiply(self) -> Self {
+    if self.a == 0.0 {
+      return Color::TRANSPARENT;
+    }
+
+    Color::new(self.r / self.a, self.g / self.a, self.b / self.a, self.a)
+  }
+
+  /// Composites this color over a background color using straight (not
+  /// premultiplied) alpha.
+  pub fn blend_over(self, background: Self) -> Self {
+    let over = self.premultiply();
+    let under = background.premultiply();
+    let inverse = 1.0 - over.a;
+
+    Color::new(
+      over.r + under.r * inverse,
+      over.g + under.g * inverse,
+      over.b + under.b * inverse,
+      over.a + under.a * inverse,
+    )
+    .unpremultiply()
+  }
+
+  /// Returns the color with each component clamped between 0 and 1.
+  pub fn clamped(self) -> Self {
+    let clamp = |c: f32| math::clamp(c, 0.0..=1.0);
+
+    Color::new(clamp(self.r), clamp(self.g), clamp(self.b), clamp(self.a))
+  }
+
+  /// Returns the components of the color rounded to 8-bit values.
+  pub fn to_rgba8(self) -> [u8; 4] {
+    let convert = |c: f32| (math::clamp(c, 0.0..=1.0) * 255.0).round() as u8;
+
+    [convert(self.r), convert(self.g), convert(self.b), convert(self.a)]
+  }
+
+  /// Returns the color as a `#rrggbb` hex string, or `#rrggbbaa` if it is not
+  /// fully opaque.
+  pub fn to_hex(self) -> String {
+    let [r, g, b, a] = self.to_rgba8();
+
+    if a == 255 {
+      format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+      format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+  }
+}
+
+impl From<[f32; 4]> for Color {
+  fn from(values: [f32; 4]) -> Self {
+    Color::new(values[0], values[1], values[2], values[3])
+  }
+}
+
+impl From<Color> for [f32; 4] {
+  fn from(color: Color) -> Self {
+    [color.r, color.g, color.b, color.a]
+  }
+}
+
+// Implement linear interpolation of each component.
+impl math::Lerp for Color {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    Color::new(
+      self.r.lerp(&other.r, t),
+      self.g.lerp(&other.g, t),
+      self.b.lerp(&other.b, t),
+      self.a.lerp(&other.a, t),
+    )
+  }
+}
+
+impl fmt::Display for Color {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(&self.to_hex())
+  }
+}
+
+// Implement serialization as a hex string when it is exact, otherwise as an
+// `[r, g, b, a]` array. Binary formats always use the array.
+
+impl serde::Serialize for Color {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let [r, g, b, a] = self.to_rgba8();
+
+    if serializer.is_human_readable() && Color::from_rgba8(r, g, b, a) == *self {
+      serializer.serialize_str(&self.to_hex())
+    } else {
+      <[f32; 4]>::from(*self).serialize(serializer)
+    }
+  }
+}
+
+impl<'de> serde::Deserialize<'de> for Color {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    if deserializer.is_human_readable() {
+      deserializer.deserialize_any(DeserializeVisitor)
+    } else {
+      Ok(<[f32; 4]>::deserialize(deserializer)?.into())
+    }
+  }
+}
+
+struct DeserializeVisitor;
+
+impl<'de> serde::de::Visitor<'de> for DeserializeVisitor {
+  type Value = Color;
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("a color such as \"#ff8000\", \"orange\", or an [r, g, b, a] array")
+  }
+
+  fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+    value.parse().map_err(E::custom)
+  }
+
+  fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+    use serde::de::Error;
+
+    let mut components = [0.0, 0.0, 0.0, 1.0];
+
+    for (i, component) in components.iter_mut().enumerate() {
+      match seq.next_element()? {
+        Some(value) => *component = value,
+        None if i == 3 => break,
+        None => return Err(A::Error::invalid_length(i, &self)),
+      }
+    }
+
+    if seq.next_element::<f32>()?.is_some() {
+      return Err(A::Error::invalid_length(5, &self));
+    }
+
+    Ok(components.into())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Asserts that each component of two colors differs by at most 1e-5.
+  fn assert_near(actual: Color, expected: Color) {
+    let diff = |a: f32, b: f32| (a - b).abs() <= 1e-5;
+
+    assert!(
+      diff(actual.r, expected.r)
+        && diff(actual.g, expected.g)
+        && diff(actual.b, expected.b)
+        && diff(actual.a, expected.a),
+      "{:?} is not near {:?}",
+      actual,
+      expected
+    );
+  }
+
+  #[test]
+  fn hex_colors_parse_in_every_length() {
+    assert_eq!(Color::parse("#f80"), Ok(Color::from_rgb_u32(0xff8800)));
+    assert_eq!(Color::parse("#f80c"), Ok(Color::from_rgba_u32(0xff8800cc)));
+    assert_eq!(Color::parse("#FF8000"), Ok(Color::from_rgb_u32(0xff8000)));
+    assert_eq!(Color::parse(" #ff800080 "), Ok(Color::from_rgba8(255, 128, 0, 128)));
+  }
+
+  #[test]
+  fn invalid_hex_colors_do_not_parse() {
+    for s in ["#", "#ff", "#fffff", "#ggg", "#+fff", "#ff80001234"] {
+      assert_eq!(Color::parse(s), Err(ParseColorError::InvalidHex(s.to_owned())));
+    }
+  }
+
+  #[test]
+  fn css_names_parse_in_any_case() {
+    assert_eq!(Color::parse("orange"), Ok(Color::from_rgb_u32(0xffa500)));
+    assert_eq!(Color::parse("RebeccaPurple"), Ok(Color::from_rgb_u32(0x663399)));
+    assert_eq!(Color::parse("transparent"), Ok(Color::TRANSPARENT));
+    assert_eq!(Color::parse("blurple"), Err(ParseColorError::UnknownName("blurple".into())));
+  }
+
+  #[test]
+  fn hex_strings_round_trip() {
+    for s in ["#ff8000", "#00000000", "#123456ab"] {
+      assert_eq!(Color::parse(s).unwrap().to_hex(), s);
+    }
+  }
+
+  #[test]
+  fn colors_round_trip_through_serde() {
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Theme {
+      color: Color,
+    }
+
+    for color in [Color::from_rgb_u32(0xff8000), Color::new(0.1, 0.2, 0.3, 0.4)] {
+      let theme = Theme { color };
+      let text = toml::to_string(&theme).unwrap();
+      let bytes = bincode::serialize(&theme).unwrap();
+
+      assert_eq!(toml::from_str::<Theme>(&text).unwrap(), theme, "{}", text);
+      assert_eq!(bytes.len(), 16);
+      assert_eq!(bincode::deserialize::<Theme>(&bytes).unwrap(), theme);
+    }
+
+    assert_eq!(toml::to_string(&Theme { color: Color::WHITE }).unwrap(), "color = \"#ffffff\"\n");
+    assert_eq!(
+      toml::from_str::<Theme>("color = \"orange\"").unwrap().color,
+      Color::from_rgb_u32(0xffa500)
+    );
+    assert_eq!(
+      toml::from_str::<Theme>("color = [1.0, 0.0, 0.0]").unwrap().color,
+      Color::rgb(1.0, 0.0, 0.0)
+    );
+  }
+
+  #[test]
+  fn colors_lerp_through_the_trait() {
+    use crate::math::Lerp;
+
+    let color = Color::BLACK.lerp(&Color::new(1.0, 0.5, 0.0, 0.0), 0.5);
+
+    assert_eq!(color, Color::new(0.5, 0.25, 0.0, 0.5));
+  }
+
+  #[test]
+  fn hsv_and_hsl_round_trip() {
+    let colors = [
+      Color::rgb(1.0, 0.5, 0.0),
+      Color::rgb(0.2, 0.4, 0.6),
+      Color::rgb(0.7, 0.1, 0.9),
+      Color::rgb(0.3, 0.8, 0.3),
+      Color::rgb(0.5, 0.5, 0.5),
+      Color::BLACK,
+      Color::WHITE,
+    ];
+
+    for color in colors {
+      let (h, s, v) = color.to_hsv();
+
+      assert_near(Color::from_hsva(h, s, v, 1.0), color);
+
+      let (h, s, l) = color.to_hsl();
+
+      assert_near(Color::from_hsla(h, s, l, 1.0), color);
+    }
+  }
+
+  #[test]
+  fn hues_wrap_around() {
+    assert_near(Color::from_hsva(0.0, 1.0, 1.0, 1.0), Color::rgb(1.0, 0.0, 0.0));
+    assert_near(Color::from_hsva(120.0, 1.0, 1.0, 1.0), Color::rgb(0.0, 1.0, 0.0));
+    assert_near(Color::from_hsva(-120.0, 1.0, 1.0, 1.0), Color::rgb(0.0, 0.0, 1.0));
+    assert_near(Color::from_hsla(420.0, 1.0, 0.5, 0.5), Color::new(1.0, 1.0, 0.0, 0.5));
+  }
+}
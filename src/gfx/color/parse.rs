@@ -0,0 +1,69 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use std::str::FromStr;
+
+impl FromStr for Color {
+  type Err = ParseColorError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.trim();
+
+    if let Some(hex) = s.strip_prefix('#') {
+      return parse_hex(hex).ok_or_else(|| ParseColorError::InvalidHex(s.to_owned()));
+    }
+
+    names::find(&s.to_ascii_lowercase()).ok_or_else(|| ParseColorError::UnknownName(s.to_owned()))
+  }
+}
+
+/// Parses the digits of a `#rgb`, `#rgba`, `#rrggbb`, or `#rrggbbaa` string.
+fn parse_hex(hex: &str) -> Option<Color> {
+  if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+    return None;
+  }
+
+  let value = u32::from_str_radix(hex, 16).ok()?;
+
+  // Expands a 4-bit digit to the 8-bit value it is short for.
+  let expand = |digit: u32| (digit & 0xf) * 0x11;
+
+  match hex.len() {
+    3 => {
+      Some(Color::from_rgb_u32(expand(value >> 8) << 16 | expand(value >> 4) << 8 | expand(value)))
+    }
+
+    4 => Some(Color::from_rgba_u32(
+      expand(value >> 12) << 24
+        | expand(value >> 8) << 16
+        | expand(value >> 4) << 8
+        | expand(value),
+    )),
+
+    6 => Some(Color::from_rgb_u32(value)),
+    8 => Some(Color::from_rgba_u32(value)),
+    _ => None,
+  }
+}
+
+/// An error that occurred while parsing a color.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseColorError {
+  /// The string starts with `#` but is not a valid hex color.
+  InvalidHex(String),
+  /// The string is not a recognized CSS color name.
+  UnknownName(String),
+}
+
+impl std::error::Error for ParseColorError {}
+
+impl fmt::Display for ParseColorError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ParseColorError::InvalidHex(s) => write!(f, "invalid hex color {:?}", s),
+      ParseColorError::UnknownName(s) => write!(f, "unknown color name {:?}", s),
+    }
+  }
+}
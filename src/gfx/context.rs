@@ -2,6 +2,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+pub use gfx_hal::adapter::AdapterInfo;
 pub use gfx_hal::error::DeviceCreationError;
 
 use super::*;
@@ -23,11 +24,16 @@ impl Context {
       "name" => backend::NAME,
     );
 
-    // Get and log all available adapters.
+    // Get and log all available adapters. Details are logged at the `trace`
+    // level, which can be enabled for this module when diagnosing a machine.
     let mut adapters = backend.enumerate_adapters();
 
     for adapter in &adapters {
+      use gfx_hal::PhysicalDevice as _;
+
       log::debug!(logger, "found graphics adapter";
+        "limits" => log::Debug(adapter.physical_device.limits()),
+        "features" => log::Debug(adapter.physical_device.features()),
         "type" => log::Debug(&adapter.info.device_type),
         "type_id" => adapter.info.device,
         "vendor_id" => adapter.info.vendor,
@@ -86,6 +92,11 @@ impl Context {
     Ok(Context { memory, queues, device, adapter, backend: backend.into() })
   }
 
+  /// Returns information about the graphics adapter in use.
+  pub fn adapter_info(&self) -> &AdapterInfo {
+    &self.adapter.info
+  }
+
   pub(super) fn backend(&self) -> &backend::Instance {
     &self.backend
   }
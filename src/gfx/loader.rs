@@ -155,7 +155,7 @@ impl Loader {
               0,
               &dest,
               cmd::ImageLayout::TransferDstOptimal,
-              Rect { start: Point2::origin(), end: Point2::new(size.width, size.height) },
+              Rect::from_size(size),
             );
 
             // Record a command to change the layout of the image for optimal
@@ -21,11 +21,12 @@ mod shader;
 mod surface;
 mod vertex;
 
+pub use self::color::*;
 pub use self::context::*;
 
 use self::{
-  buffer::*, color::*, descriptors::*, framebuffer::*, image::*, memory::*, render_pass::*,
-  sampler::*, surface::*,
+  buffer::*, descriptors::*, framebuffer::*, image::*, memory::*, render_pass::*, sampler::*,
+  surface::*,
 };
 
 use super::*;
@@ -3,6 +3,41 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use super::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Handle to a renderer running on a background thread.
+///
+/// When this structure is dropped, the renderer is stopped.
+pub struct Handle {
+  stop: Arc<AtomicBool>,
+  stopped: Option<oneshot::Receiver<()>>,
+}
+
+impl Handle {
+  /// Stops the renderer after the frame it is rendering and waits for the
+  /// device to be idle and the renderer's resources to be destroyed.
+  ///
+  /// If no backbuffer is available, such as while the window is minimized,
+  /// the renderer stops after its wait for one times out.
+  pub fn stop(mut self) {
+    self.stop_and_wait();
+  }
+
+  fn stop_and_wait(&mut self) {
+    self.stop.store(true, Ordering::Release);
+
+    if let Some(stopped) = self.stopped.take() {
+      // The channel is also canceled if the render thread panics.
+      let _ = block_on(stopped);
+    }
+  }
+}
+
+impl Drop for Handle {
+  fn drop(&mut self) {
+    self.stop_and_wait();
+  }
+}
 
 /// Starts a new renderer with the given options.
 pub fn start(
@@ -10,7 +45,7 @@ pub fn start(
   context: Arc<Context>,
   window: &window::Handle,
   logger: log::Logger,
-) -> Result<(), StartError> {
+) -> Result<Handle, StartError> {
   let context = context.clone();
   let logger = logger.clone();
 
@@ -26,14 +61,19 @@ pub fn start(
 
   framebuffer.set_render_pass(&render_pass);
 
+  let stop = Arc::new(AtomicBool::new(false));
+  let (send_stopped, recv_stopped) = oneshot::channel();
+
   // Start a thread to run the render loop.
+  let should_stop = stop.clone();
+
   thread_scope.spawn(move |_| {
     let cmd_pool = cmd_pool.into_ref_cell();
 
-    // Run the renderer indefinitely (until the window is closed).
+    // Run the renderer until it is stopped or the window is closed.
     log::info!(&logger, "renderer started");
 
-    loop {
+    while !should_stop.load(Ordering::Acquire) {
       // Render a single frame or exit the loop on failure.
       let mut render = || -> Result<(), RenderError> {
         let backbuffer = surface.acquire(&acquire_semaphore)?;
@@ -64,22 +104,40 @@ pub fn start(
         Ok(())
       };
 
-      if let Err(err) = render() {
-        if err != RenderError::BackbufferAcquireFailed(SurfaceAcquireError::WindowClosed) {
+      match render() {
+        Ok(()) => {}
+
+        // Check whether to stop before trying again.
+        Err(RenderError::BackbufferAcquireFailed(SurfaceAcquireError::TimedOut)) => {}
+
+        Err(RenderError::BackbufferAcquireFailed(SurfaceAcquireError::WindowClosed)) => break,
+
+        Err(err) => {
           log::crit!(&logger, "could not render frame: {}", err);
+          break;
         }
-
-        break;
       }
     }
 
-    // Wait for the device to be idle before shutting down.
+    // Wait for the device to be idle before shutting down, even if a frame
+    // failed after it was submitted.
     context.wait_idle();
 
+    // Destroy resources before the surface whose images they may refer to.
+    drop(framebuffer);
+    drop(render_pass);
+    drop(frame_fence);
+    drop(render_semaphore);
+    drop(acquire_semaphore);
+    drop(cmd_pool);
+    drop(surface);
+
     log::info!(&logger, "renderer stopped");
+
+    let _ = send_stopped.send(());
   });
 
-  Ok(())
+  Ok(Handle { stop, stopped: Some(recv_stopped) })
 }
 
 /// An error that occurred while starting a new renderer.
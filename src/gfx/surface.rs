@@ -25,6 +25,9 @@ impl Surface {
   /// Format of all surfaces.
   pub const FORMAT: gfx_hal::format::Format = gfx_hal::format::Format::Bgra8Unorm;
 
+  /// Maximum time in nanoseconds to wait for a backbuffer to be ready.
+  const ACQUIRE_TIMEOUT: u64 = 100_000_000;
+
   /// Creates a new surface using the given window.
   pub fn new(context: &Arc<Context>, window: &window::Handle, logger: &log::Logger) -> Self {
     let surface = context.backend().create_surface(window.as_winit());
@@ -55,6 +58,10 @@ impl Surface {
   ///
   /// If the given `signal` semaphore is provided, it will be signaled when the
   /// backbuffer is ready for use.
+  ///
+  /// Returns `SurfaceAcquireError::TimedOut` if no backbuffer is ready within
+  /// a short time, such as while the window is minimized, so that the caller
+  /// can check whether to stop before trying again.
   pub fn acquire<'a>(
     &'a mut self,
     signal: impl Into<Option<&'a cmd::Semaphore>>,
@@ -84,7 +91,9 @@ impl Surface {
     let signal = signal.into().map(cmd::Semaphore::as_backend);
 
     let index = loop {
-      let image = unsafe { self.swapchain.as_mut().unwrap().acquire_image(!0, signal, None) };
+      let image = unsafe {
+        self.swapchain.as_mut().unwrap().acquire_image(Self::ACQUIRE_TIMEOUT, signal, None)
+      };
 
       match image {
         Ok((index, None)) => {
@@ -237,6 +246,8 @@ pub enum SurfaceAcquireError {
   OutOfMemory,
   /// The window has been closed.
   WindowClosed,
+  /// No backbuffer was ready before the timeout expired.
+  TimedOut,
   /// The surface is no longer usable.
   SurfaceLost,
   /// The device is no longer usable.
@@ -251,6 +262,7 @@ impl fmt::Display for SurfaceAcquireError {
       match self {
         SurfaceAcquireError::OutOfMemory => "out of memory",
         SurfaceAcquireError::WindowClosed => "window closed",
+        SurfaceAcquireError::TimedOut => "timed out",
         SurfaceAcquireError::SurfaceLost => "surface lost",
         SurfaceAcquireError::DeviceLost => "device lost",
       }
@@ -264,10 +276,7 @@ impl From<gfx_hal::AcquireError> for SurfaceAcquireError {
       gfx_hal::AcquireError::OutOfMemory(_) => SurfaceAcquireError::OutOfMemory,
       gfx_hal::AcquireError::SurfaceLost(_) => SurfaceAcquireError::SurfaceLost,
       gfx_hal::AcquireError::DeviceLost(_) => SurfaceAcquireError::DeviceLost,
-
-      gfx_hal::AcquireError::NotReady => {
-        panic!("surface acquire timeout expired but should be infinite");
-      }
+      gfx_hal::AcquireError::NotReady => SurfaceAcquireError::TimedOut,
 
       gfx_hal::AcquireError::OutOfDate => {
         panic!("out of date surface should be handled automatically");
@@ -3,10 +3,12 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 pub mod app;
+pub mod crash;
 pub mod ecs;
 pub mod gfx;
 pub mod log;
 pub mod math;
+pub mod settings;
 pub mod time;
 pub mod util;
 pub mod vfs;
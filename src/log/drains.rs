@@ -0,0 +1,143 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::json::EscapeKeys;
+use super::*;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+/// The number of records sent to the asynchronous drain.
+pub(super) static SENT: AtomicU64 = AtomicU64::new(0);
+
+/// The number of records output by the asynchronous drain.
+pub(super) static OUTPUT: AtomicU64 = AtomicU64::new(0);
+
+/// A drain that filters log records by the current minimum level for the
+/// module they were logged in.
+///
+/// Levels are looked up through a per-thread cache, so records are filtered
+/// without locking.
+pub(super) struct ModuleFilter<D>(pub D);
+
+impl<D: Drain> Drain for ModuleFilter<D> {
+  type Ok = Option<D::Ok>;
+  type Err = D::Err;
+
+  fn log(&self, record: &Record, values: &slog::OwnedKVList) -> Result<Self::Ok, Self::Err> {
+    if record.level().is_at_least(levels::cached_level_of(record.module())) {
+      self.0.log(record, values).map(Some)
+    } else {
+      Ok(None)
+    }
+  }
+}
+
+/// A boxed drain that outputs log records in a particular format.
+pub(super) type FormatDrain = Box<dyn Drain<Ok = (), Err = slog::Never> + Send>;
+
+/// Returns a drain that outputs log records to the terminal in the given
+/// format.
+pub(super) fn terminal(format: Format) -> FormatDrain {
+  match format {
+    Format::Json => Box::new(JsonFormat::new(io::stderr()).ignore_res()),
+    format => with_decorator(format, slog_term::TermDecorator::new().build()),
+  }
+}
+
+/// Returns a drain that outputs log records to a file in the given format.
+pub(super) fn file(format: Format, file: RotatingFile) -> FormatDrain {
+  match format {
+    Format::Json => Box::new(JsonFormat::new(file).ignore_res()),
+    format => with_decorator(format, slog_term::PlainSyncDecorator::new(file)),
+  }
+}
+
+/// Returns a drain that outputs log records in the given text format with the
+/// given decorator.
+fn with_decorator<D>(format: Format, decorator: D) -> FormatDrain
+where
+  D: slog_term::Decorator + Send + 'static,
+{
+  match format {
+    Format::Compact => Box::new(slog_term::CompactFormat::new(decorator).build().ignore_res()),
+    _ => Box::new(slog_term::FullFormat::new(decorator).build().ignore_res()),
+  }
+}
+
+/// A drain that outputs log records to each of a list of drains.
+pub(super) struct Fanout(pub Vec<FormatDrain>);
+
+impl Drain for Fanout {
+  type Ok = ();
+  type Err = slog::Never;
+
+  fn log(&self, record: &Record, values: &slog::OwnedKVList) -> Result<(), slog::Never> {
+    for drain in &self.0 {
+      drain.log(record, values)?;
+    }
+
+    OUTPUT.fetch_add(1, Ordering::Release);
+
+    Ok(())
+  }
+}
+
+/// A drain that counts the records it successfully sends to the asynchronous
+/// drain so that `flush()` can wait for them to be output.
+pub(super) struct CountSent<D>(pub D);
+
+impl<D: Drain> Drain for CountSent<D> {
+  type Ok = D::Ok;
+  type Err = D::Err;
+
+  fn log(&self, record: &Record, values: &slog::OwnedKVList) -> Result<Self::Ok, Self::Err> {
+    let result = self.0.log(record, values);
+
+    if result.is_ok() {
+      SENT.fetch_add(1, Ordering::AcqRel);
+    }
+
+    result
+  }
+}
+
+/// A drain that adds the time and the name of the thread a record was logged
+/// on to its key-value pairs as `ts` and `thread` for `JsonFormat`.
+///
+/// These must be added before records are sent to the asynchronous drain,
+/// which outputs them later on another thread. The key-value pairs of the
+/// record and its logger are moved into the record with keys that collide
+/// with the fields `JsonFormat` writes prefixed by `escape_key()`.
+pub(super) struct WithTimeAndThread<D> {
+  drain: D,
+  empty: slog::OwnedKVList,
+}
+
+impl<D> WithTimeAndThread<D> {
+  /// Creates a new drain that sends records to the given drain.
+  pub fn new(drain: D) -> Self {
+    Self { drain, empty: o!().into() }
+  }
+}
+
+impl<D: Drain> Drain for WithTimeAndThread<D> {
+  type Ok = D::Ok;
+  type Err = D::Err;
+
+  fn log(&self, record: &Record, values: &slog::OwnedKVList) -> Result<Self::Ok, Self::Err> {
+    let ts = chrono::Local::now().to_rfc3339();
+    let thread = thread::current();
+
+    let kv = (
+      (EscapeKeys(record.kv()), EscapeKeys(values)),
+      (slog::SingleKV("ts", ts), slog::SingleKV("thread", thread.name().unwrap_or("unnamed"))),
+    );
+
+    let rstatic =
+      slog::RecordStatic { location: record.location(), tag: record.tag(), level: record.level() };
+
+    self.drain.log(&Record::new(&rstatic, record.msg(), slog::BorrowedKV(&kv)), &self.empty)
+  }
+}
@@ -0,0 +1,114 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use chrono::{Date, Local};
+use std::fs::File;
+use std::io::{self, Write};
+
+/// A log file in the virtual file system that is rotated by size or date.
+///
+/// When the file is rotated, it is renamed with a `.1` suffix, any previously
+/// rotated files are renamed with the next suffix, and the oldest file is
+/// removed once there are more than the number to keep.
+pub(super) struct RotatingFile {
+  vfs: vfs::Context,
+  path: vfs::PathBuf,
+  max_size: Option<u64>,
+  daily: bool,
+  keep: usize,
+  file: File,
+  size: u64,
+  date: Date<Local>,
+}
+
+impl RotatingFile {
+  /// Opens the log file for appending, rotating it first if needed.
+  pub fn open(vfs: vfs::Context, settings: &FileSettings) -> io::Result<Self> {
+    let path = vfs::PathBuf::from(settings.path.as_str());
+    let file = vfs.append(&path)?;
+    let metadata = file.metadata()?;
+
+    let date = match metadata.modified() {
+      Ok(modified) => chrono::DateTime::<Local>::from(modified).date(),
+      Err(_) => Local::today(),
+    };
+
+    let mut rotating = Self {
+      vfs,
+      path,
+      max_size: settings.max_size,
+      daily: settings.daily,
+      keep: settings.keep,
+      file,
+      size: metadata.len(),
+      date,
+    };
+
+    if rotating.size > 0 && rotating.should_rotate() {
+      rotating.rotate()?;
+    }
+
+    Ok(rotating)
+  }
+
+  /// Returns `true` if the file is too large or from an earlier date.
+  fn should_rotate(&self) -> bool {
+    self.max_size.is_some_and(|max| self.size >= max) || (self.daily && self.date != Local::today())
+  }
+
+  /// Rotates the file and opens a new, empty file in its place.
+  fn rotate(&mut self) -> io::Result<()> {
+    self.file.flush()?;
+
+    let rotated = |n: usize| vfs::PathBuf::from(format!("{}.{}", self.path, n));
+
+    if self.keep == 0 {
+      self.vfs.remove_file(&self.path)?;
+    } else {
+      ignore_not_found(self.vfs.remove_file(rotated(self.keep)))?;
+
+      for n in (1..self.keep).rev() {
+        ignore_not_found(self.vfs.rename(rotated(n), rotated(n + 1)))?;
+      }
+
+      self.vfs.rename(&self.path, rotated(1))?;
+    }
+
+    self.file = self.vfs.create(&self.path)?;
+    self.size = 0;
+    self.date = Local::today();
+
+    Ok(())
+  }
+}
+
+impl Write for RotatingFile {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    let written = self.file.write(buf)?;
+
+    self.size += written as u64;
+
+    Ok(written)
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    self.file.flush()?;
+
+    // Rotate between records, which are flushed after they are written.
+    if self.should_rotate() {
+      self.rotate()?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Returns `Ok` if the result is a "not found" error.
+fn ignore_not_found(result: io::Result<()>) -> io::Result<()> {
+  match result {
+    Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+    result => result,
+  }
+}
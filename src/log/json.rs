@@ -0,0 +1,343 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use std::io::{self, Write};
+
+/// A drain that writes each log record as a line of JSON.
+///
+/// Each line is an object with `ts`, `level`, `module`, `msg`, and `thread`
+/// fields followed by the key-value pairs of the record and its logger. The
+/// `ts` and `thread` fields are taken from key-value pairs with those keys,
+/// which `WithTimeAndThread` adds when a record is logged, and other key-value
+/// pairs with the keys of fields are skipped. Numbers, booleans, and `None`
+/// values are written as JSON values, and all other values, including `Debug`
+/// and `Display` wrappers, are written as strings.
+pub(super) struct JsonFormat<W> {
+  io: Mutex<W>,
+}
+
+impl<W: Write> JsonFormat<W> {
+  /// Creates a new drain that writes to the given writer.
+  pub fn new(io: W) -> Self {
+    Self { io: Mutex::new(io) }
+  }
+}
+
+impl<W: Write> Drain for JsonFormat<W> {
+  type Ok = ();
+  type Err = io::Error;
+
+  fn log(&self, record: &Record, values: &slog::OwnedKVList) -> io::Result<()> {
+    let mut header = HeaderSerializer::default();
+
+    slog::KV::serialize(&record.kv(), record, &mut header).map_err(to_io_error)?;
+    slog::KV::serialize(values, record, &mut header).map_err(to_io_error)?;
+
+    let ts = header.ts.unwrap_or_else(|| chrono::Local::now().to_rfc3339());
+    let mut line = String::with_capacity(256);
+
+    line.push_str("{\"ts\":");
+    push_str(&mut line, &ts);
+    line.push_str(",\"level\":");
+    push_str(&mut line, record.level().as_str());
+    line.push_str(",\"module\":");
+    push_str(&mut line, record.module());
+    line.push_str(",\"msg\":");
+    push_str(&mut line, &record.msg().to_string());
+
+    if let Some(thread) = header.thread {
+      line.push_str(",\"thread\":");
+      push_str(&mut line, &thread);
+    }
+
+    {
+      let mut serializer = JsonSerializer { line: &mut line };
+
+      slog::KV::serialize(&record.kv(), record, &mut serializer).map_err(to_io_error)?;
+      slog::KV::serialize(values, record, &mut serializer).map_err(to_io_error)?;
+    }
+
+    line.push_str("}\n");
+
+    let mut io = self.io.lock();
+
+    io.write_all(line.as_bytes())?;
+    io.flush()
+  }
+}
+
+/// The keys of the fields `JsonFormat` writes before the key-value pairs of a
+/// record.
+const FIELD_KEYS: [Key; 5] = ["ts", "level", "module", "msg", "thread"];
+
+/// Returns the given key prefixed with `kv_` if it is the key of a field
+/// `JsonFormat` writes itself.
+pub(super) fn escape_key(key: Key) -> Key {
+  match key {
+    "ts" => "kv_ts",
+    "level" => "kv_level",
+    "module" => "kv_module",
+    "msg" => "kv_msg",
+    "thread" => "kv_thread",
+    key => key,
+  }
+}
+
+/// Key-value pairs serialized with their keys escaped by `escape_key()`.
+pub(super) struct EscapeKeys<T>(pub T);
+
+impl<T: slog::KV> slog::KV for EscapeKeys<T> {
+  fn serialize(&self, record: &Record, serializer: &mut dyn Serializer) -> slog::Result {
+    self.0.serialize(record, &mut EscapeSerializer(serializer))
+  }
+}
+
+/// A serializer that passes key-value pairs to another serializer with their
+/// keys escaped by `escape_key()`.
+struct EscapeSerializer<'a>(&'a mut dyn Serializer);
+
+/// Implements serializer methods that pass values on with escaped keys.
+macro_rules! escape_values {
+  ($($method:ident: $type:ty),*) => {
+    $(
+      fn $method(&mut self, key: Key, value: $type) -> slog::Result {
+        self.0.$method(escape_key(key), value)
+      }
+    )*
+  };
+}
+
+impl<'a> Serializer for EscapeSerializer<'a> {
+  escape_values!(
+    emit_usize: usize,
+    emit_isize: isize,
+    emit_bool: bool,
+    emit_char: char,
+    emit_u8: u8,
+    emit_i8: i8,
+    emit_u16: u16,
+    emit_i16: i16,
+    emit_u32: u32,
+    emit_i32: i32,
+    emit_f32: f32,
+    emit_u64: u64,
+    emit_i64: i64,
+    emit_f64: f64,
+    emit_str: &str,
+    emit_arguments: &fmt::Arguments
+  );
+
+  fn emit_unit(&mut self, key: Key) -> slog::Result {
+    self.0.emit_unit(escape_key(key))
+  }
+
+  fn emit_none(&mut self, key: Key) -> slog::Result {
+    self.0.emit_none(escape_key(key))
+  }
+}
+
+/// A serializer that collects the values of the `ts` and `thread` key-value
+/// pairs of a record.
+#[derive(Default)]
+struct HeaderSerializer {
+  ts: Option<String>,
+  thread: Option<String>,
+}
+
+impl Serializer for HeaderSerializer {
+  fn emit_arguments(&mut self, key: Key, value: &fmt::Arguments) -> slog::Result {
+    match key {
+      "ts" => self.ts = Some(value.to_string()),
+      "thread" => self.thread = Some(value.to_string()),
+      _ => {}
+    }
+
+    Ok(())
+  }
+}
+
+/// A serializer that appends key-value pairs to a JSON object.
+struct JsonSerializer<'a> {
+  line: &'a mut String,
+}
+
+impl<'a> JsonSerializer<'a> {
+  /// Appends a key and a raw JSON value.
+  fn push_raw(&mut self, key: Key, value: &dyn fmt::Display) -> slog::Result {
+    if FIELD_KEYS.contains(&key) {
+      return Ok(());
+    }
+
+    self.line.push(',');
+    push_str(self.line, key);
+    self.line.push(':');
+
+    fmt::Write::write_fmt(self.line, format_args!("{}", value)).map_err(slog::Error::Fmt)
+  }
+}
+
+/// Implements serializer methods that write values as raw JSON numbers.
+macro_rules! emit_numbers {
+  ($($method:ident: $type:ty),*) => {
+    $(
+      fn $method(&mut self, key: Key, value: $type) -> slog::Result {
+        self.push_raw(key, &value)
+      }
+    )*
+  };
+}
+
+impl<'a> Serializer for JsonSerializer<'a> {
+  emit_numbers!(
+    emit_usize: usize,
+    emit_isize: isize,
+    emit_u8: u8,
+    emit_i8: i8,
+    emit_u16: u16,
+    emit_i16: i16,
+    emit_u32: u32,
+    emit_i32: i32,
+    emit_u64: u64,
+    emit_i64: i64,
+    emit_bool: bool
+  );
+
+  fn emit_f32(&mut self, key: Key, value: f32) -> slog::Result {
+    self.emit_f64(key, value.into())
+  }
+
+  fn emit_f64(&mut self, key: Key, value: f64) -> slog::Result {
+    // JSON has no representation for infinite or NaN numbers.
+    if value.is_finite() {
+      self.push_raw(key, &value)
+    } else {
+      self.push_raw(key, &"null")
+    }
+  }
+
+  fn emit_unit(&mut self, key: Key) -> slog::Result {
+    self.push_raw(key, &"null")
+  }
+
+  fn emit_none(&mut self, key: Key) -> slog::Result {
+    self.push_raw(key, &"null")
+  }
+
+  fn emit_str(&mut self, key: Key, value: &str) -> slog::Result {
+    if FIELD_KEYS.contains(&key) {
+      return Ok(());
+    }
+
+    self.line.push(',');
+    push_str(self.line, key);
+    self.line.push(':');
+    push_str(self.line, value);
+
+    Ok(())
+  }
+
+  fn emit_arguments(&mut self, key: Key, value: &fmt::Arguments) -> slog::Result {
+    match value.as_str() {
+      Some(value) => self.emit_str(key, value),
+      None => self.emit_str(key, &value.to_string()),
+    }
+  }
+}
+
+/// Appends a string to `line` as a quoted and escaped JSON string.
+fn push_str(line: &mut String, value: &str) {
+  line.push('"');
+
+  for c in value.chars() {
+    match c {
+      '"' => line.push_str("\\\""),
+      '\\' => line.push_str("\\\\"),
+      '\n' => line.push_str("\\n"),
+      '\r' => line.push_str("\\r"),
+      '\t' => line.push_str("\\t"),
+      c if c < ' ' => line.push_str(&format!("\\u{:04x}", c as u32)),
+      c => line.push(c),
+    }
+  }
+
+  line.push('"');
+}
+
+/// Converts a serialization error into an I/O error.
+fn to_io_error(err: slog::Error) -> io::Error {
+  match err {
+    slog::Error::Io(err) => err,
+    err => io::Error::other(format!("{:?}", err)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::drains::WithTimeAndThread;
+  use super::*;
+
+  /// A writer that appends to a buffer shared with the test.
+  #[derive(Clone, Default)]
+  struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+  impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      self.0.lock().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  impl Buffer {
+    fn output(&self) -> String {
+      String::from_utf8(self.0.lock().clone()).unwrap()
+    }
+  }
+
+  #[test]
+  fn ts_and_thread_are_taken_from_key_value_pairs() {
+    let buffer = Buffer::default();
+    let drain = JsonFormat::new(buffer.clone());
+
+    let kv = slog::b!("ts" => "2000-01-01T00:00:00+00:00", "thread" => "main");
+
+    drain.log(&slog::record!(Level::Info, "", &format_args!("hello"), kv), &o!().into()).unwrap();
+
+    assert_eq!(
+      buffer.output(),
+      "{\"ts\":\"2000-01-01T00:00:00+00:00\",\"level\":\"INFO\",\"module\":\"nova::log::json::tests\",\"msg\":\"hello\",\"thread\":\"main\"}\n"
+    );
+  }
+
+  #[test]
+  fn keys_of_fields_are_escaped_when_logged() {
+    let buffer = Buffer::default();
+    let drain = WithTimeAndThread::new(JsonFormat::new(buffer.clone()));
+
+    let kv = slog::b!("ts" => 1, "level" => 2, "thread" => 3, "id" => 4);
+
+    drain
+      .log(
+        &slog::record!(Level::Info, "", &format_args!("hello"), kv),
+        &o!("msg" => "context").into(),
+      )
+      .unwrap();
+
+    let output = buffer.output();
+
+    assert!(output.starts_with("{\"ts\":\""));
+    assert_eq!(output.matches("\"ts\":").count(), 1);
+    assert_eq!(output.matches("\"thread\":").count(), 1);
+    assert!(output.contains(",\"msg\":\"hello\",\"thread\":\""));
+
+    for pair in
+      ["\"kv_ts\":1", "\"kv_level\":2", "\"kv_thread\":3", "\"id\":4", "\"kv_msg\":\"context\""]
+    {
+      assert!(output.contains(pair), "{} is missing from {}", pair, output);
+    }
+  }
+}
@@ -0,0 +1,230 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use arc_swap::ArcSwap;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{self, AtomicUsize};
+
+lazy_static! {
+  static ref LEVELS: ArcSwap<Levels> = ArcSwap::from_pointee(Levels::default());
+}
+
+/// Incremented each time `LEVELS` changes to invalidate `CACHE`.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+  /// The levels of modules records were logged in on this thread and the
+  /// generation of `LEVELS` they were looked up in.
+  static CACHE: RefCell<(usize, HashMap<&'static str, Level>)> = RefCell::default();
+}
+
+/// The minimum levels of log records to output.
+#[derive(Debug, Clone)]
+struct Levels {
+  level: Level,
+  /// Module paths and their levels, sorted from longest to shortest path.
+  modules: Vec<(String, Level)>,
+}
+
+impl Default for Levels {
+  fn default() -> Self {
+    Self { level: Level::Trace, modules: Vec::new() }
+  }
+}
+
+impl Levels {
+  /// Returns the minimum level for records logged in the given module.
+  fn level_of(&self, module: &str) -> Level {
+    self
+      .modules
+      .iter()
+      .find(|(path, _)| {
+        module.starts_with(path.as_str())
+          && (module.len() == path.len() || module[path.len()..].starts_with("::"))
+      })
+      .map_or(self.level, |(_, level)| *level)
+  }
+
+  /// Sets the minimum level for a module, replacing any previous level.
+  fn set_module(&mut self, module: String, level: Level) {
+    self.modules.retain(|(path, _)| *path != module);
+    self.modules.push((module, level));
+    self.modules.sort_by_key(|(path, _)| cmp::Reverse(path.len()));
+  }
+}
+
+/// Replaces the current levels with a modified copy.
+fn update(modify: impl Fn(&mut Levels)) {
+  LEVELS.rcu(|levels| {
+    let mut levels = Levels::clone(levels);
+
+    modify(&mut levels);
+    levels
+  });
+
+  GENERATION.fetch_add(1, atomic::Ordering::Release);
+}
+
+/// Returns the default minimum level of log records to output.
+pub fn level() -> Level {
+  LEVELS.load().level
+}
+
+/// Sets the default minimum level of log records to output for modules
+/// without their own level.
+pub fn set_level(level: Level) {
+  update(|levels| levels.level = level);
+}
+
+/// Returns the minimum level of log records to output for the given module or
+/// one of its submodules.
+pub fn level_of(module: &str) -> Level {
+  LEVELS.load().level_of(module)
+}
+
+/// Returns the minimum level of log records to output for the given module,
+/// using a per-thread cache so that the levels are only searched the first
+/// time a module logs after they change.
+pub(super) fn cached_level_of(module: &'static str) -> Level {
+  CACHE.with(|cache| {
+    let mut cache = cache.borrow_mut();
+    let generation = GENERATION.load(atomic::Ordering::Acquire);
+
+    if cache.0 != generation {
+      cache.0 = generation;
+      cache.1.clear();
+    }
+
+    *cache.1.entry(module).or_insert_with(|| level_of(module))
+  })
+}
+
+/// Sets the minimum level of log records to output for the given module and
+/// its submodules, such as `"nova::gfx"`.
+///
+/// The level of the module with the longest matching path applies.
+pub fn set_module_level(module: impl Into<String>, level: Level) {
+  let module = module.into();
+
+  update(|levels| levels.set_module(module.clone(), level));
+}
+
+/// Removes the minimum level for the given module so that the level of its
+/// parent module or the default level applies.
+pub fn reset_module_level(module: &str) {
+  update(|levels| levels.modules.retain(|(path, _)| path != module));
+}
+
+/// Replaces all minimum levels with the levels in the given settings, such as
+/// after reloading them.
+pub fn set_levels(settings: &Settings) -> Result<(), ParseLevelError> {
+  let mut new_levels = Levels { level: parse_level(&settings.level)?, modules: Vec::new() };
+
+  for (module, level) in &settings.modules {
+    new_levels.set_module(module.clone(), parse_level(level)?);
+  }
+
+  update(|levels| *levels = new_levels.clone());
+
+  Ok(())
+}
+
+/// Sets minimum levels from a comma-separated list of directives, such as from
+/// a console command.
+///
+/// Each directive is either a level, which sets the default level, or a module
+/// path and a level separated by `=`, which sets the level of the module. For
+/// example, `"info,nova::gfx=trace"`. Levels not named in the list are not
+/// changed. If any directive is invalid, no levels are changed.
+pub fn set_filter(filter: &str) -> Result<(), ParseLevelError> {
+  let mut directives = Vec::new();
+
+  for directive in filter.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+    match directive.find('=') {
+      Some(eq) => {
+        let level = parse_level(directive[eq + 1..].trim())?;
+
+        directives.push((Some(directive[..eq].trim()), level));
+      }
+
+      None => directives.push((None, parse_level(directive)?)),
+    }
+  }
+
+  update(|levels| {
+    for (module, level) in &directives {
+      match module {
+        Some(module) => levels.set_module(module.to_string(), *level),
+        None => levels.level = *level,
+      }
+    }
+  });
+
+  Ok(())
+}
+
+/// Parses the name of a log level, such as `"info"` or `"warn"`.
+///
+/// Only the full names of levels are accepted. The `FromStr` implementation of
+/// `slog::Level` is not used because it accepts any prefix of a name and
+/// panics on `"off"` and empty strings.
+pub(super) fn parse_level(name: &str) -> Result<Level, ParseLevelError> {
+  match name {
+    "critical" => Ok(Level::Critical),
+    "error" => Ok(Level::Error),
+    "warn" | "warning" => Ok(Level::Warning),
+    "info" => Ok(Level::Info),
+    "debug" => Ok(Level::Debug),
+    "trace" => Ok(Level::Trace),
+    _ => Err(ParseLevelError(name.to_owned())),
+  }
+}
+
+/// An error returned when a log level name is not valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLevelError(pub String);
+
+impl std::error::Error for ParseLevelError {}
+
+impl fmt::Display for ParseLevelError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "invalid log level {:?}", self.0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_level_accepts_only_full_names() {
+    assert_eq!(parse_level("critical"), Ok(Level::Critical));
+    assert_eq!(parse_level("warn"), Ok(Level::Warning));
+    assert_eq!(parse_level("warning"), Ok(Level::Warning));
+    assert_eq!(parse_level("trace"), Ok(Level::Trace));
+
+    for name in &["", "off", "e", "debugger", "Info"] {
+      assert_eq!(parse_level(name), Err(ParseLevelError(name.to_string())));
+    }
+  }
+
+  #[test]
+  fn set_filter_rejects_invalid_levels_without_changing_levels() {
+    assert!(set_filter("nova::gfx=").is_err());
+    assert!(set_filter("off").is_err());
+    assert!(set_filter("debug,nova::gfx=e").is_err());
+    assert_eq!(level(), Level::Trace);
+
+    set_filter("nova::test::filter=warn").unwrap();
+
+    assert_eq!(level_of("nova::test::filter::child"), Level::Warning);
+    assert_eq!(cached_level_of("nova::test::filter"), Level::Warning);
+
+    set_module_level("nova::test::filter", Level::Error);
+
+    assert_eq!(cached_level_of("nova::test::filter"), Level::Error);
+  }
+}
@@ -7,7 +7,21 @@ pub use slog::{
   Logger, Record, Result as SerializationResult, Serializer, Value,
 };
 
+mod drains;
+mod file;
+mod json;
+mod levels;
+mod recent;
+mod settings;
+
+pub use self::{levels::*, recent::*, settings::*};
+
+use self::drains::{CountSent, Fanout, ModuleFilter, WithTimeAndThread};
+use self::file::RotatingFile;
+use self::json::JsonFormat;
+use self::recent::{Capture, RecordRing};
 use super::*;
+use std::io;
 
 lazy_static! {
   static ref GUARDS: Mutex<Option<(slog_scope::GlobalLoggerGuard, slog_async::AsyncGuard)>> =
@@ -43,23 +57,68 @@ impl<T: fmt::Display> Value for Display<T> {
   }
 }
 
-/// Initializes the logging module.
+/// Initializes the logging module with the default settings, which output
+/// log records to the terminal.
 pub fn init() {
-  let decorator = slog_term::term_full();
-  let (drain, async_guard) = slog_async::Async::new(decorator.fuse()).build_with_guard();
-  let logger = slog::Logger::root(drain.fuse(), o!());
+  init_with(&Settings::default(), &vfs::Context::new())
+    .expect("could not initialize logging with default settings");
+}
+
+/// Initializes the logging module with the given settings.
+///
+/// If the settings include a log file, it is written through the given virtual
+/// file system context.
+pub fn init_with(settings: &Settings, vfs: &vfs::Context) -> Result<(), InitError> {
+  let external_level = levels::parse_level(&settings.external_level)?;
+
+  set_levels(settings)?;
+
+  let mut outputs = Vec::new();
+
+  if settings.terminal {
+    outputs.push(drains::terminal(settings.format));
+  }
+
+  if let Some(file) = &settings.file {
+    let file = RotatingFile::open(vfs.clone(), file)?;
+
+    outputs.push(drains::file(settings.format, file));
+  }
+
+  // Keep recent records in memory for `recent()`.
+  let ring = match settings.recent {
+    0 => None,
+    capacity => Some(Arc::new(RecordRing::new(capacity))),
+  };
+
+  recent::set_ring(ring.clone());
+
+  let (drain, async_guard) = slog_async::Async::new(Fanout(outputs)).build_with_guard();
+  let drain = CountSent(drain);
+
+  // Filter and capture records before they are sent to the asynchronous drain.
+  // Discard records logged after `shut_down()` instead of panicking.
+  let logger = if settings.format == Format::Json {
+    let drain = Capture { ring, drain: WithTimeAndThread::new(drain) };
+
+    slog::Logger::root(ModuleFilter(drain).ignore_res(), o!())
+  } else {
+    slog::Logger::root(ModuleFilter(Capture { ring, drain }).ignore_res(), o!())
+  };
 
   // Expose the global logger to users of the `log` and `slog_scope` crates.
   let global_guard = slog_scope::set_global_logger(slog::Logger::root(
-    logger.clone().filter_level(Level::Warning).fuse(),
+    logger.clone().filter_level(external_level).fuse(),
     o!(),
   ));
 
   let _ = slog_stdlog::init();
 
   // Store the logger and guard.
-  *LOGGER.write() = Some(logger.clone());
+  *LOGGER.write() = Some(logger);
   *GUARDS.lock() = Some((global_guard, async_guard));
+
+  Ok(())
 }
 
 /// Returns a new `Logger` based on the default.
@@ -67,10 +126,73 @@ pub fn logger() -> Logger {
   LOGGER.read().as_ref().cloned().expect("log::init has not been called")
 }
 
+/// Returns a new `Logger` based on the default if the logging module is
+/// initialized, without blocking.
+pub(crate) fn try_logger() -> Option<Logger> {
+  LOGGER.try_read()?.as_ref().cloned()
+}
+
+/// Waits for log records logged so far to be output, for at most the given
+/// duration.
+///
+/// Returns `true` if all records were output. Unlike `shut_down()`, the
+/// logging module keeps running afterwards.
+pub fn flush(timeout: time::Duration) -> bool {
+  use std::sync::atomic::Ordering;
+
+  let sent = drains::SENT.load(Ordering::Acquire);
+  let started = time::now();
+
+  while drains::OUTPUT.load(Ordering::Acquire) < sent {
+    if started.elapsed() >= timeout {
+      return false;
+    }
+
+    time::sleep(time::seconds(0.001));
+  }
+
+  true
+}
+
 /// Flushes log records and shuts down the logging module.
 ///
 /// This function should be called before exiting the program to ensure that all
-/// log records have been output.
+/// log records have been output. Records logged after the module is shut down
+/// are discarded.
 pub fn shut_down() {
   GUARDS.lock().take();
 }
+
+/// An error that occurred while initializing the logging module.
+#[derive(Debug)]
+pub enum InitError {
+  /// A log level in the settings is not valid.
+  InvalidLevel(ParseLevelError),
+  /// The log file could not be opened.
+  Io(io::Error),
+}
+
+impl std::error::Error for InitError {}
+
+impl fmt::Display for InitError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      InitError::InvalidLevel(err) => write!(f, "{}", err),
+      InitError::Io(err) => write!(f, "could not open log file: {}", err),
+    }
+  }
+}
+
+// Implement `From` to convert from I/O errors.
+impl From<io::Error> for InitError {
+  fn from(err: io::Error) -> Self {
+    InitError::Io(err)
+  }
+}
+
+// Implement `From` to convert from invalid level errors.
+impl From<ParseLevelError> for InitError {
+  fn from(err: ParseLevelError) -> Self {
+    InitError::InvalidLevel(err)
+  }
+}
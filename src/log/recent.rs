@@ -0,0 +1,205 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use arc_swap::ArcSwapOption;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+lazy_static! {
+  static ref RING: ArcSwapOption<RecordRing> = ArcSwapOption::empty();
+}
+
+/// A log record kept in memory by the logging module.
+#[derive(Debug, Clone)]
+pub struct RecentRecord {
+  /// The local time the record was output.
+  pub time: chrono::DateTime<chrono::Local>,
+  /// The level of the record.
+  pub level: Level,
+  /// The path of the module the record was logged in.
+  pub module: &'static str,
+  /// The formatted message of the record.
+  pub message: String,
+  /// The formatted key-value pairs of the record and its logger.
+  pub values: Vec<(String, String)>,
+}
+
+impl RecentRecord {
+  /// Returns `true` if the record was logged in the given module or one of its
+  /// submodules. An empty module path matches all records.
+  pub fn is_from(&self, module: &str) -> bool {
+    self.module.starts_with(module)
+      && (self.module.len() == module.len()
+        || module.is_empty()
+        || self.module[module.len()..].starts_with("::"))
+  }
+
+  /// Returns the value of the given key if the record has one.
+  pub fn value(&self, key: &str) -> Option<&str> {
+    self.values.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+  }
+}
+
+impl fmt::Display for RecentRecord {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{} {} {}",
+      self.time.format("%b %d %H:%M:%S%.3f"),
+      self.level.as_short_str(),
+      self.message
+    )?;
+
+    for (key, value) in &self.values {
+      write!(f, ", {}: {}", key, value)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Returns the most recent log records from oldest to newest.
+///
+/// The number of records kept is set by the `recent` log setting. If the
+/// logging module is not initialized, no records are returned.
+pub fn recent() -> Vec<RecentRecord> {
+  match RING.load().as_ref() {
+    Some(ring) => ring.records(),
+    None => Vec::new(),
+  }
+}
+
+/// Returns the most recent log records of at least the given level that were
+/// logged in the given module or its submodules, from oldest to newest.
+///
+/// An empty module path matches records from all modules.
+pub fn recent_filtered(level: Level, module: &str) -> Vec<RecentRecord> {
+  let mut records = recent();
+
+  records.retain(|record| record.level.is_at_least(level) && record.is_from(module));
+  records
+}
+
+/// Removes all of the most recent log records kept in memory.
+pub fn clear_recent() {
+  if let Some(ring) = RING.load().as_ref() {
+    ring.clear();
+  }
+}
+
+/// Sets the ring of records returned by `recent()`.
+pub(super) fn set_ring(ring: Option<Arc<RecordRing>>) {
+  RING.store(ring);
+}
+
+/// A fixed-capacity, lock-free ring buffer of log records that overwrites the
+/// oldest records when full.
+pub(super) struct RecordRing {
+  slots: Box<[ArcSwapOption<(u64, RecentRecord)>]>,
+  next: AtomicU64,
+}
+
+impl RecordRing {
+  /// Creates a new, empty ring with the given capacity.
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      slots: (0..capacity.max(1)).map(|_| ArcSwapOption::empty()).collect(),
+      next: AtomicU64::new(0),
+    }
+  }
+
+  /// Adds a record, replacing the oldest record if the ring is full.
+  pub fn push(&self, record: RecentRecord) {
+    let seq = self.next.fetch_add(1, Ordering::AcqRel);
+    let slot = &self.slots[(seq % self.slots.len() as u64) as usize];
+
+    slot.store(Some(Arc::new((seq, record))));
+  }
+
+  /// Returns a copy of the records in the ring from oldest to newest.
+  pub fn records(&self) -> Vec<RecentRecord> {
+    let mut records: Vec<_> = self.slots.iter().filter_map(|slot| slot.load_full()).collect();
+
+    records.sort_by_key(|entry| entry.0);
+    records.iter().map(|entry| entry.1.clone()).collect()
+  }
+
+  /// Removes all records from the ring.
+  pub fn clear(&self) {
+    for slot in self.slots.iter() {
+      slot.store(None);
+    }
+  }
+}
+
+/// A drain that captures log records in a `RecordRing` before passing them to
+/// another drain.
+///
+/// Records are captured on the thread that logged them, so they are returned
+/// by `recent()` as soon as the logging macro returns.
+pub(super) struct Capture<D> {
+  pub ring: Option<Arc<RecordRing>>,
+  pub drain: D,
+}
+
+impl<D: Drain> Drain for Capture<D> {
+  type Ok = D::Ok;
+  type Err = D::Err;
+
+  fn log(&self, record: &Record, values: &slog::OwnedKVList) -> Result<D::Ok, D::Err> {
+    if let Some(ring) = &self.ring {
+      let mut serializer = CaptureSerializer(Vec::new());
+
+      // Formatting values into strings cannot fail.
+      let _ = slog::KV::serialize(&record.kv(), record, &mut serializer);
+      let _ = slog::KV::serialize(values, record, &mut serializer);
+
+      ring.push(RecentRecord {
+        time: chrono::Local::now(),
+        level: record.level(),
+        module: record.module(),
+        message: record.msg().to_string(),
+        values: serializer.0,
+      });
+    }
+
+    self.drain.log(record, values)
+  }
+}
+
+/// A serializer that formats key-value pairs as strings.
+struct CaptureSerializer(Vec<(String, String)>);
+
+impl Serializer for CaptureSerializer {
+  fn emit_arguments(&mut self, key: Key, value: &fmt::Arguments) -> slog::Result {
+    self.0.push((key.to_string(), value.to_string()));
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn ring_keeps_newest_records_in_order() {
+    let ring = Arc::new(RecordRing::new(3));
+    let logger = Logger::root(Capture { ring: Some(ring.clone()), drain: slog::Discard }, o!());
+
+    for i in 0..5 {
+      info!(logger, "record"; "index" => i);
+    }
+
+    // Records are captured as soon as they are logged.
+    let indices: Vec<_> =
+      ring.records().iter().map(|r| r.value("index").unwrap().to_owned()).collect();
+
+    assert_eq!(indices, ["2", "3", "4"]);
+
+    ring.clear();
+
+    assert!(ring.records().is_empty());
+  }
+}
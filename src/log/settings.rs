@@ -0,0 +1,114 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use std::collections::BTreeMap;
+
+/// Settings for logging, stored in the `[log]` section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settings {
+  /// The minimum level of log records to output, such as `"info"`.
+  ///
+  /// Defaults to `"trace"`, which outputs every record that is not removed at
+  /// compile time by the `slog` crate's maximum level features. Applications
+  /// choose those features in their own `Cargo.toml`.
+  ///
+  /// Levels can also be changed while the program is running with functions
+  /// such as `log::set_module_level()`.
+  #[serde(default = "default_level")]
+  pub level: String,
+  /// Minimum levels for specific modules, overriding `level`.
+  ///
+  /// Keys are module paths such as `"nova::gfx"`, which also match their
+  /// submodules. The longest matching path applies.
+  #[serde(default)]
+  pub modules: BTreeMap<String, String>,
+  /// The minimum level of log records from the `log` and `slog-scope` crates.
+  #[serde(default = "default_external_level")]
+  pub external_level: String,
+  /// The format of log records.
+  #[serde(default)]
+  pub format: Format,
+  /// Whether to output log records to the terminal.
+  #[serde(default = "default_terminal")]
+  pub terminal: bool,
+  /// The number of recent log records to keep in memory for `log::recent()`,
+  /// or zero to not keep any.
+  #[serde(default = "default_recent")]
+  pub recent: usize,
+  /// Settings for writing log records to a file, or `None` to not write them
+  /// to a file.
+  #[serde(default)]
+  pub file: Option<FileSettings>,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Self {
+      level: default_level(),
+      modules: BTreeMap::new(),
+      external_level: default_external_level(),
+      format: Format::default(),
+      terminal: default_terminal(),
+      recent: default_recent(),
+      file: None,
+    }
+  }
+}
+
+impl crate::settings::Section for Settings {
+  const NAME: &'static str = "log";
+}
+
+/// The format of log records.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+  /// Each record is output on one line with all of its key-value pairs.
+  #[default]
+  Full,
+  /// Records are grouped under their logger's key-value pairs, which are only
+  /// output when they change.
+  Compact,
+  /// Each record is output as a line of JSON with its timestamp, level,
+  /// module, message, thread name, and all of its key-value pairs.
+  Json,
+}
+
+/// Settings for writing log records to a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSettings {
+  /// The path of the file in the virtual file system.
+  pub path: String,
+  /// The size in bytes after which to rotate the file, or `None` to never
+  /// rotate it because of its size.
+  #[serde(default)]
+  pub max_size: Option<u64>,
+  /// Whether to rotate the file when the local date changes.
+  #[serde(default)]
+  pub daily: bool,
+  /// The number of rotated files to keep.
+  #[serde(default = "default_keep")]
+  pub keep: usize,
+}
+
+fn default_level() -> String {
+  "trace".into()
+}
+
+fn default_external_level() -> String {
+  "warning".into()
+}
+
+fn default_terminal() -> bool {
+  true
+}
+
+fn default_recent() -> usize {
+  256
+}
+
+fn default_keep() -> usize {
+  5
+}
@@ -0,0 +1,386 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Easing functions for shaping animation progress.
+//!
+//! Each function maps a linear progress value `t` from 0 to 1 to an eased
+//! progress value. Eased values start at 0 and end at 1 but may overshoot in
+//! between, as the elastic and back families do.
+
+use super::*;
+use std::f32::consts::PI;
+
+/// One of the standard easing curves.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ease {
+  /// Constant speed, no easing.
+  #[default]
+  Linear,
+  /// Eases with `quad_in()`.
+  QuadIn,
+  /// Eases with `quad_out()`.
+  QuadOut,
+  /// Eases with `quad_in_out()`.
+  QuadInOut,
+  /// Eases with `cubic_in()`.
+  CubicIn,
+  /// Eases with `cubic_out()`.
+  CubicOut,
+  /// Eases with `cubic_in_out()`.
+  CubicInOut,
+  /// Eases with `expo_in()`.
+  ExpoIn,
+  /// Eases with `expo_out()`.
+  ExpoOut,
+  /// Eases with `expo_in_out()`.
+  ExpoInOut,
+  /// Eases with `elastic_in()`.
+  ElasticIn,
+  /// Eases with `elastic_out()`.
+  ElasticOut,
+  /// Eases with `elastic_in_out()`.
+  ElasticInOut,
+  /// Eases with `back_in()`.
+  BackIn,
+  /// Eases with `back_out()`.
+  BackOut,
+  /// Eases with `back_in_out()`.
+  BackInOut,
+  /// Eases with `bounce_in()`.
+  BounceIn,
+  /// Eases with `bounce_out()`.
+  BounceOut,
+  /// Eases with `bounce_in_out()`.
+  BounceInOut,
+  /// A CSS-style cubic Bézier curve with control points `(x1, y1)` and
+  /// `(x2, y2)`.
+  CubicBezier(f32, f32, f32, f32),
+}
+
+impl Ease {
+  /// Returns the eased progress value for the linear progress value `t`.
+  pub fn apply(self, t: f32) -> f32 {
+    match self {
+      Ease::Linear => t,
+      Ease::QuadIn => quad_in(t),
+      Ease::QuadOut => quad_out(t),
+      Ease::QuadInOut => quad_in_out(t),
+      Ease::CubicIn => cubic_in(t),
+      Ease::CubicOut => cubic_out(t),
+      Ease::CubicInOut => cubic_in_out(t),
+      Ease::ExpoIn => expo_in(t),
+      Ease::ExpoOut => expo_out(t),
+      Ease::ExpoInOut => expo_in_out(t),
+      Ease::ElasticIn => elastic_in(t),
+      Ease::ElasticOut => elastic_out(t),
+      Ease::ElasticInOut => elastic_in_out(t),
+      Ease::BackIn => back_in(t),
+      Ease::BackOut => back_out(t),
+      Ease::BackInOut => back_in_out(t),
+      Ease::BounceIn => bounce_in(t),
+      Ease::BounceOut => bounce_out(t),
+      Ease::BounceInOut => bounce_in_out(t),
+      Ease::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+    }
+  }
+}
+
+/// Accelerates from zero speed with a quadratic curve.
+pub fn quad_in(t: f32) -> f32 {
+  t * t
+}
+
+/// Decelerates to zero speed with a quadratic curve.
+pub fn quad_out(t: f32) -> f32 {
+  1.0 - (1.0 - t) * (1.0 - t)
+}
+
+/// Accelerates then decelerates with a quadratic curve.
+pub fn quad_in_out(t: f32) -> f32 {
+  if t < 0.5 {
+    2.0 * t * t
+  } else {
+    1.0 - (2.0 - 2.0 * t).powi(2) / 2.0
+  }
+}
+
+/// Accelerates from zero speed with a cubic curve.
+pub fn cubic_in(t: f32) -> f32 {
+  t * t * t
+}
+
+/// Decelerates to zero speed with a cubic curve.
+pub fn cubic_out(t: f32) -> f32 {
+  1.0 - (1.0 - t).powi(3)
+}
+
+/// Accelerates then decelerates with a cubic curve.
+pub fn cubic_in_out(t: f32) -> f32 {
+  if t < 0.5 {
+    4.0 * t * t * t
+  } else {
+    1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+  }
+}
+
+/// Accelerates from zero speed with an exponential curve.
+pub fn expo_in(t: f32) -> f32 {
+  if t <= 0.0 {
+    0.0
+  } else {
+    2f32.powf(10.0 * t - 10.0)
+  }
+}
+
+/// Decelerates to zero speed with an exponential curve.
+pub fn expo_out(t: f32) -> f32 {
+  if t >= 1.0 {
+    1.0
+  } else {
+    1.0 - 2f32.powf(-10.0 * t)
+  }
+}
+
+/// Accelerates then decelerates with an exponential curve.
+pub fn expo_in_out(t: f32) -> f32 {
+  if t <= 0.0 {
+    0.0
+  } else if t >= 1.0 {
+    1.0
+  } else if t < 0.5 {
+    2f32.powf(20.0 * t - 10.0) / 2.0
+  } else {
+    (2.0 - 2f32.powf(10.0 - 20.0 * t)) / 2.0
+  }
+}
+
+/// Winds up with a growing oscillation like a stretched spring.
+pub fn elastic_in(t: f32) -> f32 {
+  if t <= 0.0 {
+    0.0
+  } else if t >= 1.0 {
+    1.0
+  } else {
+    -(2f32.powf(10.0 * t - 10.0)) * ((10.0 * t - 10.75) * (2.0 * PI / 3.0)).sin()
+  }
+}
+
+/// Overshoots and settles with a shrinking oscillation like a released
+/// spring.
+pub fn elastic_out(t: f32) -> f32 {
+  if t <= 0.0 {
+    0.0
+  } else if t >= 1.0 {
+    1.0
+  } else {
+    2f32.powf(-10.0 * t) * ((10.0 * t - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+  }
+}
+
+/// Combines `elastic_in()` and `elastic_out()`.
+pub fn elastic_in_out(t: f32) -> f32 {
+  let c = 2.0 * PI / 4.5;
+
+  if t <= 0.0 {
+    0.0
+  } else if t >= 1.0 {
+    1.0
+  } else if t < 0.5 {
+    -(2f32.powf(20.0 * t - 10.0) * ((20.0 * t - 11.125) * c).sin()) / 2.0
+  } else {
+    2f32.powf(10.0 - 20.0 * t) * ((20.0 * t - 11.125) * c).sin() / 2.0 + 1.0
+  }
+}
+
+/// The amount `back_in()` and `back_out()` overshoot by.
+const BACK_OVERSHOOT: f32 = 1.701_58;
+
+/// Pulls back slightly before accelerating.
+pub fn back_in(t: f32) -> f32 {
+  (BACK_OVERSHOOT + 1.0) * t * t * t - BACK_OVERSHOOT * t * t
+}
+
+/// Overshoots slightly before settling.
+pub fn back_out(t: f32) -> f32 {
+  let t = t - 1.0;
+
+  1.0 + (BACK_OVERSHOOT + 1.0) * t * t * t + BACK_OVERSHOOT * t * t
+}
+
+/// Combines `back_in()` and `back_out()`.
+pub fn back_in_out(t: f32) -> f32 {
+  let c = BACK_OVERSHOOT * 1.525;
+
+  if t < 0.5 {
+    (2.0 * t).powi(2) * ((c + 1.0) * 2.0 * t - c) / 2.0
+  } else {
+    ((2.0 * t - 2.0).powi(2) * ((c + 1.0) * (2.0 * t - 2.0) + c) + 2.0) / 2.0
+  }
+}
+
+/// Bounces off the start like a ball dropped in reverse.
+pub fn bounce_in(t: f32) -> f32 {
+  1.0 - bounce_out(1.0 - t)
+}
+
+/// Bounces to a stop at the end like a dropped ball.
+pub fn bounce_out(t: f32) -> f32 {
+  const N: f32 = 7.5625;
+  const D: f32 = 2.75;
+
+  if t < 1.0 / D {
+    N * t * t
+  } else if t < 2.0 / D {
+    let t = t - 1.5 / D;
+    N * t * t + 0.75
+  } else if t < 2.5 / D {
+    let t = t - 2.25 / D;
+    N * t * t + 0.9375
+  } else {
+    let t = t - 2.625 / D;
+    N * t * t + 0.984_375
+  }
+}
+
+/// Combines `bounce_in()` and `bounce_out()`.
+pub fn bounce_in_out(t: f32) -> f32 {
+  if t < 0.5 {
+    (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0
+  } else {
+    (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0
+  }
+}
+
+/// Eases along a cubic Bézier curve from `(0, 0)` to `(1, 1)` with control
+/// points `(x1, y1)` and `(x2, y2)`, like the CSS `cubic-bezier()` function.
+///
+/// The `x` values are clamped between 0 and 1 so that the curve is a function
+/// of time. The progress value `t` is clamped between 0 and 1.
+pub fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, t: f32) -> f32 {
+  let x1 = math::clamp(x1, 0.0..=1.0);
+  let x2 = math::clamp(x2, 0.0..=1.0);
+  let t = math::clamp(t, 0.0..=1.0);
+
+  // Evaluates one dimension of the curve and its derivative at `s`.
+  let sample = |p1: f32, p2: f32, s: f32| {
+    let inv = 1.0 - s;
+
+    3.0 * inv * inv * s * p1 + 3.0 * inv * s * s * p2 + s * s * s
+  };
+
+  let slope = |p1: f32, p2: f32, s: f32| {
+    let inv = 1.0 - s;
+
+    3.0 * inv * inv * p1 + 6.0 * inv * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+  };
+
+  // Find the curve parameter where `x` equals `t` with Newton's method, then
+  // fall back to bisection if it does not converge.
+  let mut s = t;
+
+  for _ in 0..8 {
+    let error = sample(x1, x2, s) - t;
+
+    if error.abs() < 1e-6 {
+      return sample(y1, y2, s);
+    }
+
+    let d = slope(x1, x2, s);
+
+    if d.abs() < 1e-6 {
+      break;
+    }
+
+    s -= error / d;
+  }
+
+  let (mut low, mut high) = (0.0, 1.0);
+
+  s = t;
+
+  for _ in 0..32 {
+    let x = sample(x1, x2, s);
+
+    if (x - t).abs() < 1e-6 {
+      break;
+    }
+
+    if x < t {
+      low = s;
+    } else {
+      high = s;
+    }
+
+    s = (low + high) / 2.0;
+  }
+
+  sample(y1, y2, s)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ALL: [Ease; 21] = [
+    Ease::Linear,
+    Ease::QuadIn,
+    Ease::QuadOut,
+    Ease::QuadInOut,
+    Ease::CubicIn,
+    Ease::CubicOut,
+    Ease::CubicInOut,
+    Ease::ExpoIn,
+    Ease::ExpoOut,
+    Ease::ExpoInOut,
+    Ease::ElasticIn,
+    Ease::ElasticOut,
+    Ease::ElasticInOut,
+    Ease::BackIn,
+    Ease::BackOut,
+    Ease::BackInOut,
+    Ease::BounceIn,
+    Ease::BounceOut,
+    Ease::BounceInOut,
+    Ease::CubicBezier(0.25, 0.1, 0.25, 1.0),
+    Ease::CubicBezier(0.7, -0.5, 0.3, 1.5),
+  ];
+
+  fn assert_near(actual: f32, expected: f32, ease: Ease) {
+    assert!((actual - expected).abs() < 1e-5, "{:?} gave {} instead of {}", ease, actual, expected);
+  }
+
+  #[test]
+  fn curves_start_at_zero_and_end_at_one() {
+    for ease in ALL {
+      assert_near(ease.apply(0.0), 0.0, ease);
+      assert_near(ease.apply(1.0), 1.0, ease);
+    }
+  }
+
+  #[test]
+  fn in_out_curves_are_halfway_at_the_midpoint() {
+    for ease in [
+      Ease::Linear,
+      Ease::QuadInOut,
+      Ease::CubicInOut,
+      Ease::ExpoInOut,
+      Ease::BackInOut,
+      Ease::BounceInOut,
+    ] {
+      assert_near(ease.apply(0.5), 0.5, ease);
+    }
+  }
+
+  #[test]
+  fn cubic_beziers_with_linear_control_points_are_linear() {
+    let ease = Ease::CubicBezier(0.25, 0.25, 0.75, 0.75);
+
+    for t in [0.1, 0.3, 0.5, 0.9] {
+      assert_near(ease.apply(t), t, ease);
+    }
+
+    assert_near(ease.apply(2.0), 1.0, ease);
+  }
+}
@@ -0,0 +1,51 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+
+/// Trait for values that can be linearly interpolated, such as by a
+/// `time::Tween`.
+pub trait Lerp {
+  /// Returns the value `t` of the way from this value to `other`.
+  ///
+  /// A `t` of 0 returns this value and a `t` of 1 returns `other`. Values
+  /// outside that range extrapolate.
+  fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    self + (other - self) * t
+  }
+}
+
+impl Lerp for f64 {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    self + (other - self) * t as f64
+  }
+}
+
+impl Lerp for Point2<f32> {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    Point2::new(self.x.lerp(&other.x, t), self.y.lerp(&other.y, t))
+  }
+}
+
+impl Lerp for Vector2<f32> {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    Vector2::new(self.x.lerp(&other.x, t), self.y.lerp(&other.y, t))
+  }
+}
+
+impl Lerp for Size<f32> {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    Size::new(self.width.lerp(&other.width, t), self.height.lerp(&other.height, t))
+  }
+}
+
+impl Lerp for Rect<f32> {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    Rect::new(self.start.lerp(&other.start, t), self.end.lerp(&other.end, t))
+  }
+}
@@ -5,9 +5,13 @@
 //! The `math` module exposes common functions and types for working with
 //! scalar numbers, vectors, matrices, and geometric dimensions.
 
+pub mod ease;
+
+mod lerp;
 mod rect;
 mod size;
 
+pub use self::lerp::Lerp;
 pub use self::rect::Rect;
 pub use self::size::Size;
 pub use nalgebra::Matrix4;
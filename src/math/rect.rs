@@ -2,9 +2,13 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use super::{Point2, ScalarNum};
+use super::*;
+use num_traits::AsPrimitive;
 
 /// Represents a two-dimensional rectangle in space.
+///
+/// A rectangle contains the points from its start point up to but not
+/// including its end point, so rectangles that share an edge do not intersect.
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Rect<T: ScalarNum> {
@@ -16,6 +20,21 @@ pub struct Rect<T: ScalarNum> {
 }
 
 impl<T: ScalarNum> Rect<T> {
+  /// Creates a new rectangle from its start and end points.
+  pub fn new(start: Point2<T>, end: Point2<T>) -> Self {
+    Rect { start, end }
+  }
+
+  /// Creates a new rectangle with the given start point and size.
+  pub fn from_origin_size(origin: Point2<T>, size: Size<T>) -> Self {
+    Rect { start: origin, end: Point2::new(origin.x + size.width, origin.y + size.height) }
+  }
+
+  /// Creates a new rectangle of the given size starting at the origin.
+  pub fn from_size(size: Size<T>) -> Self {
+    Self::from_origin_size(Point2::new(T::zero(), T::zero()), size)
+  }
+
   /// Returns the width of the rectangle.
   pub fn width(&self) -> T {
     self.end.x - self.start.x
@@ -25,4 +44,220 @@ impl<T: ScalarNum> Rect<T> {
   pub fn height(&self) -> T {
     self.end.y - self.start.y
   }
+
+  /// Returns the size of the rectangle.
+  pub fn size(&self) -> Size<T> {
+    Size::new(self.width(), self.height())
+  }
+
+  /// Returns the area of the rectangle.
+  pub fn area(&self) -> T {
+    self.width() * self.height()
+  }
+
+  /// Returns the center point of the rectangle.
+  pub fn center(&self) -> Point2<T> {
+    let two = T::one() + T::one();
+
+    Point2::new((self.start.x + self.end.x) / two, (self.start.y + self.end.y) / two)
+  }
+
+  /// Returns the rectangle moved by the given offset.
+  pub fn translate(&self, offset: Vector2<T>) -> Self {
+    Rect {
+      start: Point2::new(self.start.x + offset.x, self.start.y + offset.y),
+      end: Point2::new(self.end.x + offset.x, self.end.y + offset.y),
+    }
+  }
+
+  /// Returns the rectangle with its start and end points scaled by the given
+  /// factor relative to the origin.
+  pub fn scale(&self, factor: T) -> Self {
+    Rect {
+      start: Point2::new(self.start.x * factor, self.start.y * factor),
+      end: Point2::new(self.end.x * factor, self.end.y * factor),
+    }
+  }
+
+  /// Returns the rectangle with each edge moved inward by the given amount.
+  pub fn inset(&self, amount: T) -> Self {
+    Rect {
+      start: Point2::new(self.start.x + amount, self.start.y + amount),
+      end: Point2::new(self.end.x - amount, self.end.y - amount),
+    }
+  }
+
+  /// Returns the rectangle with each edge moved outward by the given amount.
+  pub fn outset(&self, amount: T) -> Self {
+    Rect {
+      start: Point2::new(self.start.x - amount, self.start.y - amount),
+      end: Point2::new(self.end.x + amount, self.end.y + amount),
+    }
+  }
+
+  /// Converts the rectangle to another scalar type like an `as` cast.
+  pub fn cast<U>(&self) -> Rect<U>
+  where
+    T: AsPrimitive<U>,
+    U: ScalarNum + Copy,
+  {
+    Rect {
+      start: Point2::new(self.start.x.as_(), self.start.y.as_()),
+      end: Point2::new(self.end.x.as_(), self.end.y.as_()),
+    }
+  }
+}
+
+impl<T: ScalarNum + PartialOrd> Rect<T> {
+  /// Returns `true` if the rectangle has no area because its width or height
+  /// is zero or negative.
+  pub fn is_empty(&self) -> bool {
+    self.end.x <= self.start.x || self.end.y <= self.start.y
+  }
+
+  /// Returns `true` if the rectangle contains the given point.
+  pub fn contains(&self, point: Point2<T>) -> bool {
+    point.x >= self.start.x
+      && point.y >= self.start.y
+      && point.x < self.end.x
+      && point.y < self.end.y
+  }
+
+  /// Returns `true` if the rectangle entirely contains another non-empty
+  /// rectangle.
+  pub fn contains_rect(&self, other: &Self) -> bool {
+    !other.is_empty()
+      && other.start.x >= self.start.x
+      && other.start.y >= self.start.y
+      && other.end.x <= self.end.x
+      && other.end.y <= self.end.y
+  }
+
+  /// Returns `true` if the rectangle and another rectangle overlap.
+  pub fn intersects(&self, other: &Self) -> bool {
+    self.intersection(other).is_some()
+  }
+
+  /// Returns the overlapping area of the rectangle and another rectangle, or
+  /// `None` if they do not overlap.
+  pub fn intersection(&self, other: &Self) -> Option<Self> {
+    let rect = Rect {
+      start: Point2::new(max(self.start.x, other.start.x), max(self.start.y, other.start.y)),
+      end: Point2::new(min(self.end.x, other.end.x), min(self.end.y, other.end.y)),
+    };
+
+    if rect.is_empty() {
+      None
+    } else {
+      Some(rect)
+    }
+  }
+
+  /// Returns the smallest rectangle that contains both the rectangle and
+  /// another rectangle.
+  ///
+  /// Empty rectangles are ignored unless both are empty.
+  pub fn union(&self, other: &Self) -> Self {
+    if other.is_empty() {
+      return *self;
+    }
+
+    if self.is_empty() {
+      return *other;
+    }
+
+    Rect {
+      start: Point2::new(min(self.start.x, other.start.x), min(self.start.y, other.start.y)),
+      end: Point2::new(max(self.end.x, other.end.x), max(self.end.y, other.end.y)),
+    }
+  }
+}
+
+impl<T: ScalarNum> ops::Mul<T> for Rect<T> {
+  type Output = Self;
+
+  fn mul(self, factor: T) -> Self {
+    self.scale(factor)
+  }
+}
+
+// Implement serialization as start and end points, each an `[x, y]` array.
+#[derive(Serialize, Deserialize)]
+struct SerializedRect<T> {
+  start: (T, T),
+  end: (T, T),
+}
+
+impl<T: serde::Serialize + ScalarNum> serde::Serialize for Rect<T> {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let rect =
+      SerializedRect { start: (self.start.x, self.start.y), end: (self.end.x, self.end.y) };
+
+    rect.serialize(serializer)
+  }
+}
+
+impl<'de, T: serde::Deserialize<'de> + ScalarNum> serde::Deserialize<'de> for Rect<T> {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let rect = SerializedRect::<T>::deserialize(deserializer)?;
+
+    Ok(Rect {
+      start: Point2::new(rect.start.0, rect.start.1),
+      end: Point2::new(rect.end.0, rect.end.1),
+    })
+  }
+}
+
+/// Returns the lesser of two partially ordered values.
+fn min<T: PartialOrd>(a: T, b: T) -> T {
+  if b < a {
+    b
+  } else {
+    a
+  }
+}
+
+/// Returns the greater of two partially ordered values.
+fn max<T: PartialOrd>(a: T, b: T) -> T {
+  if b > a {
+    b
+  } else {
+    a
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rect(x1: i32, y1: i32, x2: i32, y2: i32) -> Rect<i32> {
+    Rect::new(Point2::new(x1, y1), Point2::new(x2, y2))
+  }
+
+  #[test]
+  fn intersections_are_the_overlapping_area() {
+    assert_eq!(rect(0, 0, 10, 10).intersection(&rect(5, -5, 15, 5)), Some(rect(5, 0, 10, 5)));
+    assert_eq!(rect(0, 0, 10, 10).intersection(&rect(2, 2, 4, 4)), Some(rect(2, 2, 4, 4)));
+    assert!(rect(0, 0, 10, 10).intersects(&rect(9, 9, 20, 20)));
+  }
+
+  #[test]
+  fn rectangles_sharing_an_edge_do_not_intersect() {
+    assert_eq!(rect(0, 0, 10, 10).intersection(&rect(10, 0, 20, 10)), None);
+    assert_eq!(rect(0, 0, 10, 10).intersection(&rect(20, 20, 30, 30)), None);
+    assert!(!rect(0, 0, 10, 10).intersects(&rect(0, 10, 10, 20)));
+    assert!(!rect(0, 0, 10, 10).intersects(&rect(5, 5, 5, 5)));
+  }
+
+  #[test]
+  fn unions_contain_both_rectangles() {
+    assert_eq!(rect(0, 0, 10, 10).union(&rect(20, -5, 30, 5)), rect(0, -5, 30, 10));
+    assert_eq!(rect(0, 0, 10, 10).union(&rect(2, 2, 4, 4)), rect(0, 0, 10, 10));
+  }
+
+  #[test]
+  fn unions_ignore_empty_rectangles() {
+    assert_eq!(rect(0, 0, 10, 10).union(&rect(50, 50, 50, 60)), rect(0, 0, 10, 10));
+    assert_eq!(rect(50, 50, 40, 60).union(&rect(0, 0, 10, 10)), rect(0, 0, 10, 10));
+  }
 }
@@ -3,6 +3,7 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use super::*;
+use num_traits::AsPrimitive;
 
 /// A two-dimensional size.
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -19,6 +20,39 @@ impl<T: ScalarNum> Size<T> {
   pub fn new(width: T, height: T) -> Self {
     Size { width, height }
   }
+
+  /// Returns the area of the size, its width multiplied by its height.
+  pub fn area(&self) -> T {
+    self.width * self.height
+  }
+
+  /// Returns the ratio of the width to the height, such as the aspect ratio
+  /// of a screen.
+  ///
+  /// The ratio is computed in floating point, so integer sizes are not
+  /// truncated. A zero height gives an infinite or NaN ratio.
+  pub fn ratio(&self) -> f64
+  where
+    T: AsPrimitive<f64>,
+  {
+    self.width.as_() / self.height.as_()
+  }
+
+  /// Converts the size to another scalar type like an `as` cast.
+  pub fn cast<U>(&self) -> Size<U>
+  where
+    T: AsPrimitive<U>,
+    U: ScalarNum + Copy,
+  {
+    Size { width: self.width.as_(), height: self.height.as_() }
+  }
+}
+
+impl<T: ScalarNum + PartialOrd> Size<T> {
+  /// Returns `true` if the width or height is zero or negative.
+  pub fn is_empty(&self) -> bool {
+    self.width <= T::zero() || self.height <= T::zero()
+  }
 }
 
 impl<T: ScalarNum> Default for Size<T> {
@@ -39,6 +73,22 @@ impl<T: ScalarNum> fmt::Debug for Size<T> {
   }
 }
 
+impl<T: ScalarNum> ops::Add for Size<T> {
+  type Output = Self;
+
+  fn add(self, other: Self) -> Self {
+    Size { width: self.width + other.width, height: self.height + other.height }
+  }
+}
+
+impl<T: ScalarNum> ops::Sub for Size<T> {
+  type Output = Self;
+
+  fn sub(self, other: Self) -> Self {
+    Size { width: self.width - other.width, height: self.height - other.height }
+  }
+}
+
 impl<T: ScalarNum> ops::Mul<T> for Size<T> {
   type Output = Self;
 
@@ -47,6 +97,14 @@ impl<T: ScalarNum> ops::Mul<T> for Size<T> {
   }
 }
 
+impl<T: ScalarNum> ops::Div<T> for Size<T> {
+  type Output = Self;
+
+  fn div(self, divisor: T) -> Self {
+    Size { width: self.width / divisor, height: self.height / divisor }
+  }
+}
+
 impl<T: serde::Serialize + ScalarNum> serde::Serialize for Size<T> {
   fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
     (self.width, self.height).serialize(serializer)
@@ -60,3 +118,21 @@ impl<'de, T: serde::Deserialize<'de> + ScalarNum> serde::Deserialize<'de> for Si
     Ok(Size { width, height })
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn ratios_of_integer_sizes_are_not_truncated() {
+    assert_eq!(Size::<u32>::new(1920, 1080).ratio(), 1920.0 / 1080.0);
+    assert_eq!(Size::<i32>::new(3, 4).ratio(), 0.75);
+    assert_eq!(Size::<f32>::new(2.0, 1.0).ratio(), 2.0);
+  }
+
+  #[test]
+  fn ratios_of_sizes_with_zero_height_do_not_panic() {
+    assert_eq!(Size::<u32>::new(16, 0).ratio(), f64::INFINITY);
+    assert!(Size::<u32>::new(0, 0).ratio().is_nan());
+  }
+}
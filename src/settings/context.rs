@@ -0,0 +1,368 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use std::collections::BTreeMap;
+use std::env;
+use std::io::Write as _;
+use toml::value::{Table, Value};
+
+/// A settings context containing values loaded from each `Layer`.
+///
+/// Values are read by merging all layers in order, so that values in later
+/// layers override values in earlier layers. Values set with `set()` are stored
+/// in the `User` layer and can be written back to the user settings file with
+/// `save()`.
+#[derive(Default)]
+pub struct Context {
+  layers: [Table; 5],
+  user_file: Option<vfs::PathBuf>,
+  changes: BTreeMap<Vec<String>, Value>,
+}
+
+impl Context {
+  /// Creates a new, empty settings context.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Registers a settings section, adding its default values to the
+  /// `Defaults` layer.
+  ///
+  /// Values already loaded into the `Defaults` layer take precedence over the
+  /// defaults of the section.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the default value of the section cannot be serialized as TOML.
+  pub fn register<S: Section>(&mut self) {
+    let defaults = Value::try_from(S::default()).expect("could not serialize default settings");
+    let mut table = Table::new();
+
+    table.insert(S::NAME.to_owned(), defaults);
+    table::merge(&mut table, &self.layers[Layer::Defaults as usize]);
+
+    self.layers[Layer::Defaults as usize] = table;
+  }
+
+  /// Loads settings from a string containing TOML into the given layer.
+  pub fn load_str(&mut self, layer: Layer, source: &str) -> Result<(), LoadError> {
+    let table = toml::from_str(source)?;
+
+    table::merge(&mut self.layers[layer as usize], &table);
+
+    Ok(())
+  }
+
+  /// Loads settings from a TOML file in the virtual file system into the given
+  /// layer. If the file does not exist, no settings are loaded.
+  ///
+  /// If the layer is `Layer::User`, the file becomes the user settings file
+  /// that `save()` writes to, even if it does not exist yet.
+  pub fn load_file(
+    &mut self,
+    vfs: &vfs::Context,
+    path: impl Into<vfs::PathBuf>,
+    layer: Layer,
+  ) -> Result<(), LoadError> {
+    let path = path.into();
+
+    let result = match vfs.read_to_string(&path) {
+      Ok(source) => self.load_str(layer, &source),
+      Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+      Err(err) => Err(err.into()),
+    };
+
+    if layer == Layer::User {
+      self.user_file = Some(path);
+    }
+
+    result
+  }
+
+  /// Loads settings from environment variables into the `Env` layer.
+  ///
+  /// Only variables named with the given `prefix` followed by an underscore are
+  /// loaded. The rest of the name is the lowercase key path with keys separated
+  /// by double underscores, so `GAME_WINDOW__TITLE` sets `window.title` if the
+  /// prefix is `GAME`.
+  pub fn load_env(&mut self, prefix: &str) {
+    let prefix = format!("{}_", prefix);
+
+    for (name, value) in env::vars_os() {
+      let (name, value) = match (name.to_str(), value.to_str()) {
+        (Some(name), Some(value)) => (name.to_owned(), value.to_owned()),
+        _ => continue,
+      };
+
+      if !name.starts_with(&prefix) {
+        continue;
+      }
+
+      let path = name[prefix.len()..].split("__").map(str::to_lowercase).collect::<Vec<_>>();
+
+      table::set(&mut self.layers[Layer::Env as usize], &path, table::parse_value(&value));
+    }
+  }
+
+  /// Loads settings from `--set key=value` command-line arguments into the
+  /// `Args` layer. Other arguments are ignored.
+  ///
+  /// Keys are dotted key paths such as `window.title`. Values are parsed as
+  /// TOML if possible and are otherwise treated as strings.
+  pub fn load_args<I>(&mut self, args: I) -> Result<(), LoadError>
+  where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+  {
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+      let arg = arg.as_ref();
+
+      let assignment = if arg == "--set" {
+        args
+          .next()
+          .map(|next| next.as_ref().to_owned())
+          .ok_or_else(|| LoadError::InvalidArg(arg.into()))?
+      } else if let Some(assignment) = arg.strip_prefix("--set=") {
+        assignment.to_owned()
+      } else {
+        continue;
+      };
+
+      let eq = assignment.find('=').ok_or_else(|| LoadError::InvalidArg(assignment.clone()))?;
+      let path = table::parse_path(&assignment[..eq]);
+
+      if path.iter().any(String::is_empty) {
+        return Err(LoadError::InvalidArg(assignment));
+      }
+
+      table::set(
+        &mut self.layers[Layer::Args as usize],
+        &path,
+        table::parse_value(&assignment[eq + 1..]),
+      );
+    }
+
+    Ok(())
+  }
+
+  /// Returns the value at a dotted key path such as `window.title` with all
+  /// layers applied.
+  pub fn value(&self, key: &str) -> Option<Value> {
+    table::get(&self.merged(), &table::parse_path(key)).cloned()
+  }
+
+  /// Returns all settings with all layers applied as TOML text.
+  pub fn to_toml(&self) -> Result<String, SerializeError> {
+    toml::to_string(&self.merged())
+  }
+
+  /// Returns the settings section of type `S` with all layers applied.
+  ///
+  /// If no layer has values for the section, its default value is returned.
+  pub fn get<S: Section>(&self) -> Result<S, DeserializeError> {
+    match self.merged().remove(S::NAME) {
+      Some(value) => value.try_into(),
+      None => Ok(S::default()),
+    }
+  }
+
+  /// Sets any values of the settings section of type `S` that differ from the
+  /// current settings in the `User` layer.
+  ///
+  /// Values are compared with the settings of the layers up to and including
+  /// the `User` layer, so a value equal to an environment variable or
+  /// command-line override is still set and saved. This includes overridden
+  /// values read with `get()` and passed back unchanged.
+  ///
+  /// Changed values are written to the user settings file by `save()`. Values
+  /// that are not serialized, such as options set to `None`, are left as they
+  /// are so that unknown values in the section are not lost.
+  pub fn set<S: Section>(&mut self, section: &S) -> Result<(), SerializeError> {
+    let new = match Value::try_from(section)? {
+      Value::Table(table) => table,
+      _ => Table::new(),
+    };
+
+    let old = match self.merged_through(Layer::User).remove(S::NAME) {
+      Some(Value::Table(table)) => table,
+      _ => Table::new(),
+    };
+
+    let user = &mut self.layers[Layer::User as usize];
+    let changes = &mut self.changes;
+
+    table::diff(&mut vec![S::NAME.to_owned()], &old, &new, &mut |path, value| {
+      table::set(user, path, value.clone());
+      changes.insert(path.to_vec(), value.clone());
+    });
+
+    Ok(())
+  }
+
+  /// Returns `true` if any values have been changed with `set()` but have not
+  /// been saved.
+  pub fn has_unsaved_changes(&self) -> bool {
+    !self.changes.is_empty()
+  }
+
+  /// Writes values changed with `set()` to the user settings file.
+  ///
+  /// Only the lines of changed values are modified. Comments and all other
+  /// values in the file are left as they are.
+  pub fn save(&mut self, vfs: &vfs::Context) -> Result<(), SaveError> {
+    if self.changes.is_empty() {
+      return Ok(());
+    }
+
+    let path = self.user_file.as_ref().ok_or(SaveError::NoUserFile)?;
+
+    let mut source = match vfs.read_to_string(path) {
+      Ok(source) => source,
+      Err(ref err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+      Err(err) => return Err(err.into()),
+    };
+
+    for (path, value) in &self.changes {
+      let cannot_patch = || SaveError::CannotPatch(path.join("."));
+
+      source = patch::patch(&source, path, value).ok_or_else(cannot_patch)?;
+
+      // Make sure the patched file is still valid and has the new value.
+      let patched = toml::from_str::<Table>(&source).map_err(|_| cannot_patch())?;
+
+      if table::get(&patched, path) != Some(value) {
+        return Err(cannot_patch());
+      }
+    }
+
+    vfs.create(path)?.write_all(source.as_bytes())?;
+
+    self.changes.clear();
+
+    Ok(())
+  }
+
+  /// Returns a table containing the values of all layers merged in order.
+  fn merged(&self) -> Table {
+    self.merged_through(Layer::Args)
+  }
+
+  /// Returns a table containing the values of the layers up to and including
+  /// `last` merged in order.
+  fn merged_through(&self, last: Layer) -> Table {
+    let mut merged = Table::new();
+
+    for layer in &self.layers[..=last as usize] {
+      table::merge(&mut merged, layer);
+    }
+
+    merged
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_derive::*;
+  use std::fs;
+
+  #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+  struct Window {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    width: u32,
+  }
+
+  impl Section for Window {
+    const NAME: &'static str = "window";
+  }
+
+  #[test]
+  fn later_layers_override_earlier_layers() {
+    let mut settings = Context::new();
+
+    settings.register::<Window>();
+    settings.load_str(Layer::App, "[window]\ntitle = \"App\"\nwidth = 800").unwrap();
+    settings.load_str(Layer::Args, "[window]\ntitle = \"Args\"").unwrap();
+    settings.load_str(Layer::User, "[window]\ntitle = \"User\"\nwidth = 1024").unwrap();
+
+    assert_eq!(settings.get::<Window>().unwrap(), Window { title: "Args".into(), width: 1024 });
+    assert_eq!(settings.value("window.width"), Some(Value::Integer(1024)));
+    assert_eq!(settings.value("window.height"), None);
+  }
+
+  #[test]
+  fn env_vars_are_loaded_into_the_env_layer() {
+    env::set_var("NOVA_SETTINGS_TEST_WINDOW__TITLE", "Hello, world");
+    env::set_var("NOVA_SETTINGS_TEST_WINDOW__WIDTH", "1280");
+    env::set_var("NOVA_SETTINGS_TESTS_WINDOW__WIDTH", "1");
+
+    let mut settings = Context::new();
+
+    settings.load_str(Layer::User, "[window]\nwidth = 800").unwrap();
+    settings.load_env("NOVA_SETTINGS_TEST");
+
+    assert_eq!(settings.value("window.title"), Some(Value::String("Hello, world".into())));
+    assert_eq!(settings.value("window.width"), Some(Value::Integer(1280)));
+  }
+
+  #[test]
+  fn args_are_loaded_into_the_args_layer() {
+    let mut settings = Context::new();
+
+    settings
+      .load_args(["game", "--set", "window.width=640", "--verbose", "--set=window.title=A=B"])
+      .unwrap();
+
+    settings.load_env("NOVA_SETTINGS_TEST_UNUSED");
+
+    assert_eq!(settings.value("window.width"), Some(Value::Integer(640)));
+    assert_eq!(settings.value("window.title"), Some(Value::String("A=B".into())));
+
+    assert!(matches!(settings.load_args(["--set"]), Err(LoadError::InvalidArg(_))));
+    assert!(matches!(settings.load_args(["--set=window"]), Err(LoadError::InvalidArg(_))));
+    assert!(matches!(settings.load_args(["--set=window..title=1"]), Err(LoadError::InvalidArg(_))));
+  }
+
+  #[test]
+  fn values_equal_to_overrides_are_saved() {
+    let dir = env::temp_dir().join(format!("nova-settings-test-{}", std::process::id()));
+
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("settings.toml"), "# Window\n[window]\ntitle = \"User\"\n").unwrap();
+
+    let vfs = vfs::Context::new();
+
+    vfs.mount("/", &dir);
+
+    let mut settings = Context::new();
+
+    settings.register::<Window>();
+    settings.load_file(&vfs, "/settings.toml", Layer::User).unwrap();
+    settings.load_args(["--set", "window.title=Args"]).unwrap();
+
+    let mut window = Window { title: "User".into(), width: 0 };
+
+    settings.set(&window).unwrap();
+
+    assert!(!settings.has_unsaved_changes());
+
+    window.title = "Args".into();
+    settings.set(&window).unwrap();
+
+    assert!(settings.has_unsaved_changes());
+
+    settings.save(&vfs).unwrap();
+
+    let saved = fs::read_to_string(dir.join("settings.toml")).unwrap();
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(saved, "# Window\n[window]\ntitle = \"Args\"\n");
+  }
+}
@@ -0,0 +1,122 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The `settings` module loads application settings from layered sources and
+//! saves changes made by the user back to the user settings file.
+
+mod context;
+mod patch;
+mod table;
+
+pub use self::context::*;
+
+use super::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io;
+
+/// An error that occurred while deserializing a settings section.
+pub type DeserializeError = toml::de::Error;
+
+/// An error that occurred while serializing a settings section.
+pub type SerializeError = toml::ser::Error;
+
+/// A typed section of settings stored in a TOML table.
+///
+/// Modules implement this trait for their settings structs and register them
+/// with `Context::register()` so that their defaults are known.
+pub trait Section: Default + Serialize + DeserializeOwned {
+  /// Name of the TOML table containing the section.
+  const NAME: &'static str;
+}
+
+/// One of the layers settings are loaded from.
+///
+/// Values in later layers override values in earlier layers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+  /// Built-in defaults, including the defaults of registered sections.
+  Defaults,
+  /// Settings shipped with the application.
+  App,
+  /// Settings overridden by the user. Changes are saved to this layer.
+  User,
+  /// Settings from environment variables.
+  Env,
+  /// Settings from command-line arguments.
+  Args,
+}
+
+impl Layer {
+  /// All layers in the order in which they are applied.
+  pub const ALL: [Layer; 5] = [Layer::Defaults, Layer::App, Layer::User, Layer::Env, Layer::Args];
+}
+
+/// An error that occurred while loading settings.
+#[derive(Debug)]
+pub enum LoadError {
+  /// An error occurred while reading a settings file.
+  Io(io::Error),
+  /// A settings file contained invalid TOML.
+  Parse(toml::de::Error),
+  /// A command-line argument was not a valid `--set key=value` argument.
+  InvalidArg(String),
+}
+
+impl std::error::Error for LoadError {}
+
+impl fmt::Display for LoadError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      LoadError::Io(cause) => write!(f, "failed to read settings: {}", cause),
+      LoadError::Parse(cause) => write!(f, "failed to parse settings: {}", cause),
+      LoadError::InvalidArg(arg) => write!(f, "invalid settings argument {:?}", arg),
+    }
+  }
+}
+
+// Implement `From` to convert from io errors.
+impl From<io::Error> for LoadError {
+  fn from(cause: io::Error) -> Self {
+    LoadError::Io(cause)
+  }
+}
+
+// Implement `From` to convert from TOML parse errors.
+impl From<toml::de::Error> for LoadError {
+  fn from(cause: toml::de::Error) -> Self {
+    LoadError::Parse(cause)
+  }
+}
+
+/// An error that occurred while saving settings.
+#[derive(Debug)]
+pub enum SaveError {
+  /// No user settings file has been loaded to save changes to.
+  NoUserFile,
+  /// An error occurred while reading or writing the user settings file.
+  Io(io::Error),
+  /// A changed value could not be written to the user settings file without
+  /// rewriting unrelated parts of it.
+  CannotPatch(String),
+}
+
+impl std::error::Error for SaveError {}
+
+impl fmt::Display for SaveError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SaveError::NoUserFile => write!(f, "no user settings file"),
+      SaveError::Io(cause) => write!(f, "failed to write settings: {}", cause),
+      SaveError::CannotPatch(key) => write!(f, "cannot update {:?} in the user settings file", key),
+    }
+  }
+}
+
+// Implement `From` to convert from io errors.
+impl From<io::Error> for SaveError {
+  fn from(cause: io::Error) -> Self {
+    SaveError::Io(cause)
+  }
+}
@@ -0,0 +1,318 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use toml::value::{Table, Value};
+
+/// Sets the value at the given key path in TOML `source` text, leaving all
+/// other lines including comments unchanged.
+///
+/// Returns `None` if the value cannot be set without rewriting other lines.
+pub fn patch(source: &str, path: &[String], value: &Value) -> Option<String> {
+  let (key, table_path) = path.split_last()?;
+  let mut lines = source.lines().map(String::from).collect::<Vec<_>>();
+
+  // Find the range of lines in the table and the lines of the key's entry. The
+  // root table starts on the first line and has no header.
+  let mut in_table = table_path.is_empty();
+  let mut table_range = if in_table { Some((0, None)) } else { None };
+  let mut key_range = None;
+
+  for entry in scan(&lines) {
+    match entry.kind {
+      EntryKind::Header(header_path) => {
+        if in_table {
+          if let Some((_, end)) = &mut table_range {
+            *end = Some(entry.start);
+          }
+        }
+
+        in_table = header_path.as_deref() == Some(table_path);
+
+        if in_table {
+          // Tables defined twice are invalid, so leave them alone.
+          if table_range.is_some() {
+            return None;
+          }
+
+          table_range = Some((entry.start + 1, None));
+        }
+      }
+
+      EntryKind::Key(entry_key) => {
+        if in_table && entry_key == *key {
+          key_range = Some((entry.start, entry.count));
+        }
+      }
+
+      EntryKind::Other => {}
+    }
+  }
+
+  match (table_range, key_range) {
+    // Replace the existing entry, keeping any comment after its value.
+    (_, Some((start, count))) => {
+      let (_, comment) = measure_value(&lines[start..])?;
+
+      // Keep the original indentation and formatting of the key.
+      let (_, after_key) = split_key(lines[start].trim_start())?;
+      let key_end = lines[start].len() - after_key.len();
+      let mut line = format!("{} = {}", &lines[start][..key_end], value);
+
+      if let Some(comment) = comment {
+        line.push(' ');
+        line.push_str(&comment);
+      }
+
+      lines.splice(start..start + count, Some(line));
+    }
+
+    // Add an entry after the last non-blank line of the existing table.
+    (Some((start, end)), None) => {
+      let end = end.unwrap_or(lines.len());
+      let index =
+        (start..end).rev().find(|i| !lines[*i].trim().is_empty()).map_or(start, |i| i + 1);
+
+      lines.insert(index, format_entry(key, value));
+    }
+
+    // Add a new table with the entry at the end.
+    (None, None) => {
+      if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+        lines.push(String::new());
+      }
+
+      let header = table_path.iter().map(|key| format_key(key)).collect::<Vec<_>>().join(".");
+
+      lines.push(format!("[{}]", header));
+      lines.push(format_entry(key, value));
+    }
+  }
+
+  let mut patched = lines.join("\n");
+
+  patched.push('\n');
+
+  Some(patched)
+}
+
+/// A line or group of lines in a TOML document.
+struct Entry {
+  /// Index of the first line.
+  start: usize,
+  /// Number of lines.
+  count: usize,
+  kind: EntryKind,
+}
+
+enum EntryKind {
+  /// A table header with its key path, or `None` for an array of tables.
+  Header(Option<Vec<String>>),
+  /// A key and its value, which may span multiple lines.
+  Key(String),
+  /// A blank line, a comment, or a line that could not be parsed.
+  Other,
+}
+
+/// Splits the lines of a TOML document into entries.
+fn scan(lines: &[String]) -> Vec<Entry> {
+  let mut entries = Vec::new();
+  let mut start = 0;
+
+  while start < lines.len() {
+    let line = lines[start].trim();
+
+    let (kind, count) = if line.starts_with('[') {
+      (EntryKind::Header(parse_header(line)), 1)
+    } else if let Some(key) = parse_key(line) {
+      match measure_value(&lines[start..]) {
+        Some((count, _)) => (EntryKind::Key(key), count),
+        None => (EntryKind::Other, 1),
+      }
+    } else {
+      (EntryKind::Other, 1)
+    };
+
+    entries.push(Entry { start, count, kind });
+    start += count;
+  }
+
+  entries
+}
+
+/// Returns the number of lines spanned by the key and value starting on the
+/// first line, and the comment after the value if there is one.
+fn measure_value(lines: &[String]) -> Option<(usize, Option<String>)> {
+  let mut text = String::new();
+
+  for (index, line) in lines.iter().enumerate() {
+    if index > 0 {
+      text.push('\n');
+    }
+
+    let line_start = text.len();
+
+    text.push_str(line);
+
+    let parsed = match toml::from_str::<Table>(&text) {
+      Ok(parsed) => parsed,
+      Err(_) => continue,
+    };
+
+    // Find the first `#` on the last line that begins a comment, meaning the
+    // text before it parses to the same value.
+    let comment = line
+      .match_indices('#')
+      .map(|(offset, _)| line_start + offset)
+      .find(|end| toml::from_str::<Table>(&text[..*end]).ok().as_ref() == Some(&parsed))
+      .map(|end| text[end..].to_owned());
+
+    return Some((index + 1, comment));
+  }
+
+  None
+}
+
+/// Parses the key path of a table header line, or returns `None` if the line
+/// is the header of an array of tables.
+fn parse_header(line: &str) -> Option<Vec<String>> {
+  if line.starts_with("[[") {
+    return None;
+  }
+
+  let mut rest = line[1..].trim_start();
+  let mut path = Vec::new();
+
+  loop {
+    let (key, after) = split_key(rest)?;
+
+    path.push(key);
+    rest = after.trim_start();
+
+    if rest.starts_with('.') {
+      rest = rest[1..].trim_start();
+    } else if rest.starts_with(']') {
+      return Some(path);
+    } else {
+      return None;
+    }
+  }
+}
+
+/// Parses the key of a `key = value` line. Returns `None` if the line is not a
+/// key and value or has a dotted key.
+fn parse_key(line: &str) -> Option<String> {
+  let (key, rest) = split_key(line)?;
+
+  if rest.trim_start().starts_with('=') {
+    Some(key)
+  } else {
+    None
+  }
+}
+
+/// Splits a bare or quoted key from the beginning of `text`.
+fn split_key(text: &str) -> Option<(String, &str)> {
+  if text.starts_with('"') || text.starts_with('\'') {
+    let quote = text.as_bytes()[0] as char;
+    let mut escaped = false;
+
+    for (offset, c) in text.char_indices().skip(1) {
+      if c == quote && !escaped {
+        let key = toml::from_str::<Table>(&format!("key = {}", &text[..=offset])).ok()?;
+
+        return Some((key.get("key")?.as_str()?.to_owned(), &text[offset + 1..]));
+      }
+
+      escaped = quote == '"' && c == '\\' && !escaped;
+    }
+
+    return None;
+  }
+
+  let end = text.find(|c: char| !is_bare_key_char(c)).unwrap_or(text.len());
+
+  if end == 0 {
+    return None;
+  }
+
+  Some((text[..end].to_owned(), &text[end..]))
+}
+
+/// Formats a `key = value` line.
+fn format_entry(key: &str, value: &Value) -> String {
+  format!("{} = {}", format_key(key), value)
+}
+
+/// Formats a key, quoting it if it is not a valid bare key.
+fn format_key(key: &str) -> String {
+  if !key.is_empty() && key.chars().all(is_bare_key_char) {
+    key.to_owned()
+  } else {
+    Value::String(key.to_owned()).to_string()
+  }
+}
+
+/// Returns `true` if the character is allowed in a bare key.
+fn is_bare_key_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn path(keys: &[&str]) -> Vec<String> {
+    keys.iter().map(|key| key.to_string()).collect()
+  }
+
+  #[test]
+  fn replaced_values_keep_other_lines_and_comments() {
+    let source =
+      "# Settings\nname = \"old\"  # the name\n\n[window]\n  width = 800 # pixels\nheight = 600\n";
+
+    let patched = patch(source, &path(&["window", "width"]), &Value::Integer(1024)).unwrap();
+
+    assert_eq!(
+      patched,
+      "# Settings\nname = \"old\"  # the name\n\n[window]\n  width = 1024 # pixels\nheight = 600\n"
+    );
+
+    let patched = patch(&patched, &path(&["name"]), &Value::String("new".into())).unwrap();
+
+    assert_eq!(
+      patched,
+      "# Settings\nname = \"new\" # the name\n\n[window]\n  width = 1024 # pixels\nheight = 600\n"
+    );
+  }
+
+  #[test]
+  fn multi_line_values_are_replaced_with_one_line() {
+    let source = "list = [\n  1, # one\n  2,\n]\nafter = true\n";
+    let value = Value::Array(vec![Value::Integer(3)]);
+
+    assert_eq!(patch(source, &path(&["list"]), &value).unwrap(), "list = [3]\nafter = true\n");
+  }
+
+  #[test]
+  fn missing_entries_are_added_to_their_table() {
+    let source = "[audio]\nvolume = 0.5\n\n[window]\nwidth = 800\n";
+
+    assert_eq!(
+      patch(source, &path(&["audio", "muted"]), &Value::Boolean(true)).unwrap(),
+      "[audio]\nvolume = 0.5\nmuted = true\n\n[window]\nwidth = 800\n"
+    );
+
+    assert_eq!(
+      patch(source, &path(&["input", "key map"]), &Value::String("qwerty".into())).unwrap(),
+      "[audio]\nvolume = 0.5\n\n[window]\nwidth = 800\n\n[input]\n\"key map\" = \"qwerty\"\n"
+    );
+  }
+
+  #[test]
+  fn tables_defined_twice_are_not_patched() {
+    let source = "[window]\nwidth = 800\n\n[window]\nheight = 600\n";
+
+    assert_eq!(patch(source, &path(&["window", "width"]), &Value::Integer(1)), None);
+  }
+}
@@ -0,0 +1,90 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use toml::value::{Table, Value};
+
+/// Merges `upper` into `lower`, replacing values in `lower` unless both values
+/// are tables, which are merged recursively.
+pub fn merge(lower: &mut Table, upper: &Table) {
+  for (key, value) in upper {
+    match (lower.get_mut(key), value) {
+      (Some(Value::Table(lower)), Value::Table(upper)) => merge(lower, upper),
+
+      _ => {
+        lower.insert(key.clone(), value.clone());
+      }
+    }
+  }
+}
+
+/// Returns the value at the given key path in the table.
+pub fn get<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+  let (key, parents) = path.split_last()?;
+  let mut table = table;
+
+  for parent in parents {
+    table = table.get(parent)?.as_table()?;
+  }
+
+  table.get(key)
+}
+
+/// Sets the value at the given key path in the table, replacing any non-table
+/// values in the way with tables.
+pub fn set(table: &mut Table, path: &[String], value: Value) {
+  let (key, parents) = match path.split_last() {
+    Some(split) => split,
+    None => return,
+  };
+
+  let mut table = table;
+
+  for parent in parents {
+    let entry = table.entry(parent.clone()).or_insert_with(|| Value::Table(Table::new()));
+
+    if !entry.is_table() {
+      *entry = Value::Table(Table::new());
+    }
+
+    table = entry.as_table_mut().expect("value is not a table");
+  }
+
+  table.insert(key.clone(), value);
+}
+
+/// Calls `func` with the key path and value of each non-table value in `new`
+/// that differs from the value at the same path in `old`.
+pub fn diff(
+  path: &mut Vec<String>,
+  old: &Table,
+  new: &Table,
+  func: &mut impl FnMut(&[String], &Value),
+) {
+  for (key, new_value) in new {
+    path.push(key.clone());
+
+    match (old.get(key), new_value) {
+      (Some(Value::Table(old)), Value::Table(new)) => diff(path, old, new, func),
+      (_, Value::Table(new)) => diff(path, &Table::new(), new, func),
+      (Some(old_value), _) if old_value == new_value => {}
+      _ => func(path, new_value),
+    }
+
+    path.pop();
+  }
+}
+
+/// Parses a dotted key path such as `window.title`.
+pub fn parse_path(key: &str) -> Vec<String> {
+  key.split('.').map(|part| part.trim().to_owned()).collect()
+}
+
+/// Parses a string from the environment or command line as a TOML value,
+/// treating it as a string if it is not valid TOML.
+pub fn parse_value(source: &str) -> Value {
+  toml::from_str::<Table>(&format!("value = {}", source))
+    .ok()
+    .and_then(|mut table| table.remove("value"))
+    .unwrap_or_else(|| Value::String(source.to_owned()))
+}
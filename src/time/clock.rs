@@ -5,24 +5,54 @@
 use super::*;
 
 /// Tracks time across individual calls to a `tick()` function.
-#[derive(Default, Debug)]
+///
+/// Elapsed time is scaled by the clock's time scale and does not advance
+/// while the clock is paused. When an interval is set, the clock also counts
+/// elapsed intervals for running fixed-timestep updates.
+///
+/// The current time is read from a `TimeSource`, which is `RealTime` unless
+/// another source is set with `set_time_source()`.
+#[derive(Debug)]
 pub struct Clock {
+  source: Arc<dyn TimeSource>,
   ticked_at: Option<Instant>,
   interval: Duration,
   intervals_synchronized: bool,
+  max_fixed_steps: u64,
+  time_scale: f64,
+  paused: bool,
   elapsed: Duration,
   elapsed_delta: Duration,
+  unscaled_elapsed_delta: Duration,
   elapsed_intervals: u64,
   elapsed_intervals_delta: u64,
   elapsed_interval_remainder: Duration,
 }
 
 impl Clock {
+  /// The default maximum number of fixed steps per tick.
+  pub const DEFAULT_MAX_FIXED_STEPS: u64 = 8;
+
   /// Creates a new clock.
   pub fn new() -> Self {
     Self::default()
   }
 
+  /// Returns the source of the current time used by the clock.
+  pub fn time_source(&self) -> &dyn TimeSource {
+    &*self.source
+  }
+
+  /// Sets the source of the current time used by the clock, such as a
+  /// `VirtualTime` for deterministic tests.
+  ///
+  /// Because instants from different sources cannot be compared, this resets
+  /// the clock so that the next tick is treated as the first.
+  pub fn set_time_source(&mut self, source: impl TimeSource + 'static) {
+    self.source = Arc::new(source);
+    self.ticked_at = None;
+  }
+
   /// Returns the instant and time when the clock was last ticked.
   ///
   /// # Panics
@@ -55,6 +85,51 @@ impl Clock {
     self.intervals_synchronized = value;
   }
 
+  /// Returns the maximum number of fixed steps returned by `fixed_steps()`,
+  /// or zero if there is no maximum.
+  pub fn max_fixed_steps(&self) -> u64 {
+    self.max_fixed_steps
+  }
+
+  /// Sets the maximum number of fixed steps to run per tick.
+  ///
+  /// If more intervals elapse in one tick, the extra intervals are skipped so
+  /// that slow updates cannot fall further and further behind. Set to zero to
+  /// never skip intervals. The default is `DEFAULT_MAX_FIXED_STEPS`.
+  pub fn set_max_fixed_steps(&mut self, value: u64) {
+    self.max_fixed_steps = value;
+  }
+
+  /// Returns the rate at which elapsed time passes relative to real time.
+  pub fn time_scale(&self) -> f64 {
+    self.time_scale
+  }
+
+  /// Sets the rate at which elapsed time passes relative to real time, such
+  /// as `0.5` for half speed. The default is `1.0`.
+  ///
+  /// # Panics
+  ///
+  /// This function panics if `value` is negative or not finite.
+  pub fn set_time_scale(&mut self, value: f64) {
+    assert!(value >= 0.0 && value.is_finite(), "time scale must be a non-negative number");
+
+    self.time_scale = value;
+  }
+
+  /// Returns `true` if the clock is paused.
+  pub fn is_paused(&self) -> bool {
+    self.paused
+  }
+
+  /// Sets whether the clock is paused.
+  ///
+  /// While paused, the clock can still be ticked but no time or intervals
+  /// elapse.
+  pub fn set_paused(&mut self, value: bool) {
+    self.paused = value;
+  }
+
   /// Returns the total elapsed duration as of the most recent tick, or zero
   /// if the clock has not been ticked.
   pub fn elapsed(&self) -> Duration {
@@ -64,7 +139,13 @@ impl Clock {
   /// Returns the duration of time elapsed between the last two ticks, or zero
   /// if the clock has not been ticked twice.
   pub fn elapsed_delta(&self) -> Duration {
-    self.elapsed
+    self.elapsed_delta
+  }
+
+  /// Returns the real duration of time between the last two ticks, ignoring
+  /// the time scale and whether the clock is paused.
+  pub fn unscaled_elapsed_delta(&self) -> Duration {
+    self.unscaled_elapsed_delta
   }
 
   /// Returns the total number of intervals elapsed as of the most recent tick.
@@ -83,6 +164,59 @@ impl Clock {
     self.elapsed_intervals_delta
   }
 
+  /// Returns the number of fixed-timestep updates to run for the most recent
+  /// tick.
+  ///
+  /// This is the number of elapsed intervals, limited to the maximum set with
+  /// `set_max_fixed_steps()`. If no interval is set, this is always one so
+  /// that updates run once per tick.
+  pub fn fixed_steps(&self) -> u64 {
+    if self.interval == Duration::ZERO {
+      return 1;
+    }
+
+    match self.max_fixed_steps {
+      0 => self.elapsed_intervals_delta,
+      max => self.elapsed_intervals_delta.min(max),
+    }
+  }
+
+  /// Returns the duration of game time each update should advance by.
+  ///
+  /// This is the interval when one is set, since updates then run once per
+  /// fixed step. Otherwise it is the elapsed delta of the most recent tick.
+  pub fn step_delta(&self) -> Duration {
+    if self.interval == Duration::ZERO {
+      self.elapsed_delta
+    } else {
+      self.interval
+    }
+  }
+
+  /// Returns the duration of game time covered by all updates of the most
+  /// recent tick, which is `step_delta()` times `fixed_steps()`.
+  ///
+  /// Systems that run once per frame, such as in the `BeforeUpdate` phase,
+  /// can advance by this duration to stay in step with the updates. It is
+  /// zero while the clock is paused.
+  pub fn updates_delta(&self) -> Duration {
+    self.step_delta() * self.fixed_steps() as f64
+  }
+
+  /// Returns the fraction of an interval that has elapsed since the last
+  /// fixed step, from `0.0` up to but not including `1.0`.
+  ///
+  /// This can be used to interpolate between the previous and current state
+  /// of fixed-timestep updates when rendering. If no interval is set, this is
+  /// always zero.
+  pub fn alpha(&self) -> f64 {
+    if self.interval == Duration::ZERO {
+      return 0.0;
+    }
+
+    (self.elapsed_interval_remainder.as_secs() / self.interval.as_secs()).min(1.0)
+  }
+
   /// Updates the values tracked by the clock based on the current time.
   ///
   /// If the clock is set to synchronize intervals, this method will block until
@@ -95,30 +229,42 @@ impl Clock {
       Some(then) => then,
 
       None => {
-        self.ticked_at = Some(now());
+        self.ticked_at = Some(self.source.now());
         return;
       }
     };
 
-    // Compute the ideal elapsed delta, which is the time remaining until
-    // another interval elapses.
-    let ideal_elapsed_delta = self.interval - self.elapsed_interval_remainder;
+    let time_scale = if self.paused { 0.0 } else { self.time_scale };
+
+    // Compute the ideal elapsed delta, which is the real time remaining until
+    // another interval elapses. Never wait longer than one interval so that
+    // ticks stay regular when time is slowed down or paused.
+    let ideal_elapsed_delta = if time_scale > 0.0 {
+      let remaining = (self.interval - self.elapsed_interval_remainder).as_secs() / time_scale;
+
+      seconds(remaining.min(self.interval.as_secs()))
+    } else {
+      self.interval
+    };
 
     // Compute the actual elapsed delta.
-    let mut ticked_at = now();
-    let mut elapsed_delta = ticked_at - prev_ticked_at;
+    let mut ticked_at = self.source.now();
+    let mut unscaled_elapsed_delta = ticked_at - prev_ticked_at;
 
     // If the clock is synchronized, try to wait for the ideal delta time to
     // elapse.
-    if self.intervals_synchronized && elapsed_delta < ideal_elapsed_delta {
-      spin_sleep(ideal_elapsed_delta - elapsed_delta);
+    if self.intervals_synchronized && unscaled_elapsed_delta < ideal_elapsed_delta {
+      self.source.sleep(ideal_elapsed_delta - unscaled_elapsed_delta);
 
-      ticked_at = now();
-      elapsed_delta = ticked_at - prev_ticked_at;
+      ticked_at = self.source.now();
+      unscaled_elapsed_delta = ticked_at - prev_ticked_at;
     }
 
     self.ticked_at = Some(ticked_at);
 
+    let mut elapsed_delta = unscaled_elapsed_delta * time_scale;
+
+    self.unscaled_elapsed_delta = unscaled_elapsed_delta;
     self.elapsed_delta = elapsed_delta;
     self.elapsed += elapsed_delta;
 
@@ -132,7 +278,57 @@ impl Clock {
       self.elapsed_interval_remainder = elapsed_delta - self.interval * elapsed_intervals_delta;
       self.elapsed_intervals += self.elapsed_intervals_delta;
     } else {
-      self.elapsed_intervals = 0;
+      self.elapsed_intervals_delta = 0;
+      self.elapsed_interval_remainder = Duration::ZERO;
+    }
+  }
+}
+
+impl Default for Clock {
+  fn default() -> Self {
+    Self {
+      source: Arc::new(RealTime),
+      ticked_at: None,
+      interval: Duration::ZERO,
+      intervals_synchronized: false,
+      max_fixed_steps: Self::DEFAULT_MAX_FIXED_STEPS,
+      time_scale: 1.0,
+      paused: false,
+      elapsed: Duration::ZERO,
+      elapsed_delta: Duration::ZERO,
+      unscaled_elapsed_delta: Duration::ZERO,
+      elapsed_intervals: 0,
+      elapsed_intervals_delta: 0,
+      elapsed_interval_remainder: Duration::ZERO,
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn paused_clock_runs_no_fixed_steps() {
+    let time = VirtualTime::new();
+    let mut clock = Clock::new();
+
+    clock.set_time_source(time.clone());
+    clock.set_interval(seconds(0.1));
+    clock.tick();
+
+    time.advance(seconds(0.25));
+    clock.tick();
+
+    assert_eq!(clock.fixed_steps(), 2);
+    assert_eq!(clock.updates_delta(), seconds(0.2));
+    assert!((clock.alpha() - 0.5).abs() < 1e-9);
+
+    clock.set_paused(true);
+    time.advance(seconds(1.0));
+    clock.tick();
+
+    assert_eq!(clock.fixed_steps(), 0);
+    assert_eq!(clock.updates_delta(), Duration::ZERO);
+  }
+}
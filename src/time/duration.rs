@@ -3,6 +3,8 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use super::*;
+use std::num::ParseFloatError;
+use std::str::FromStr;
 
 /// Represents a span of time.
 ///
@@ -26,6 +28,67 @@ impl Duration {
   pub fn as_secs(self) -> f64 {
     self.seconds
   }
+
+  /// Adds another duration, returning an error if the result is not finite.
+  pub fn checked_add(self, other: Duration) -> Result<Duration, DurationError> {
+    try_seconds(self.seconds + other.seconds)
+  }
+
+  /// Subtracts another duration, returning an error if the result is negative
+  /// instead of clamping it to zero.
+  pub fn checked_sub(self, other: Duration) -> Result<Duration, DurationError> {
+    try_seconds(self.seconds - other.seconds)
+  }
+
+  /// Multiplies the duration by a value, returning an error if the result is
+  /// negative or not finite.
+  pub fn checked_mul(self, value: f64) -> Result<Duration, DurationError> {
+    try_seconds(self.seconds * value)
+  }
+
+  /// Divides the duration by a value, returning an error if the result is
+  /// negative or not finite.
+  pub fn checked_div(self, value: f64) -> Result<Duration, DurationError> {
+    try_seconds(self.seconds / value)
+  }
+
+  /// Formats the duration in the largest unit it is at least one of, rounded
+  /// to the given number of decimal places or in full if `None`.
+  ///
+  /// When formatted in full, parsing the output results in the same duration.
+  fn fmt_in_unit(self, f: &mut fmt::Formatter, precision: Option<usize>) -> fmt::Result {
+    let secs = self.seconds;
+
+    if secs == 0.0 || (precision.is_some() && secs < 0.000_000_000_001) {
+      return write!(f, "0");
+    }
+
+    let (unit, scale) = if secs < 0.000_001 {
+      ("ns", 0.000_000_001)
+    } else if secs < 0.001 {
+      ("μs", 0.000_001)
+    } else if secs < 1.0 {
+      ("ms", 0.001)
+    } else if secs < 60.0 {
+      ("s", 1.0)
+    } else if secs < 3_600.0 {
+      ("m", 60.0)
+    } else if secs < 86_400.0 {
+      ("h", 3_600.0)
+    } else {
+      ("d", 86_400.0)
+    };
+
+    let value = secs / scale;
+
+    match precision {
+      Some(precision) => write!(f, "{:.*}{}", precision, value, unit),
+      // Parsing multiplies by the scale, which may not exactly undo dividing
+      // by it, so fall back to seconds when needed.
+      None if value * scale == secs => write!(f, "{}{}", value, unit),
+      None => write!(f, "{}s", secs),
+    }
+  }
 }
 
 // Implement conversion to and from standard library durations.
@@ -93,25 +156,7 @@ impl ops::Mul<f64> for Duration {
 // Implement formatting and logging traits to display human-readable durations.
 impl fmt::Debug for Duration {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    let secs = self.seconds;
-
-    if secs < 0.000_000_000_001 {
-      write!(f, "0")
-    } else if secs < 0.000_001 {
-      write!(f, "{:.3}ns", secs * 1_000_000_000.0)
-    } else if secs < 0.001 {
-      write!(f, "{:.3}μs", secs * 1_000_000.0)
-    } else if secs < 1.0 {
-      write!(f, "{:.3}ms", secs * 1_000.0)
-    } else if secs < 60.0 {
-      write!(f, "{:.3}s", secs)
-    } else if secs < 3_600.0 {
-      write!(f, "{:.3}m", secs / 60.0)
-    } else if secs < 86_400.0 {
-      write!(f, "{:.3}h", secs / 3_600.0)
-    } else {
-      write!(f, "{:.3}d", secs / 86_400.0)
-    }
+    self.fmt_in_unit(f, Some(3))
   }
 }
 
@@ -132,24 +177,266 @@ impl log::Value for Duration {
   }
 }
 
+// Implement parsing from strings like "16.6ms", "60hz", or "2s".
+impl FromStr for Duration {
+  type Err = ParseDurationError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.trim();
+
+    if let Some(value) = s.strip_suffix("hz") {
+      return Ok(try_hz(value.trim_end().parse()?)?);
+    }
+
+    let (value, scale) = UNITS
+      .iter()
+      .find_map(|(unit, scale)| Some((s.strip_suffix(unit)?, *scale)))
+      .unwrap_or((s, 1.0));
+
+    let value = value.trim_end();
+
+    // The only unit-less value `Display` outputs is "0", but any number of
+    // seconds is accepted.
+    let value: f64 = value.parse().map_err(|err| {
+      if value.ends_with(|c: char| c.is_alphabetic()) {
+        ParseDurationError::UnknownUnit(s.to_owned())
+      } else {
+        ParseDurationError::InvalidNumber(err)
+      }
+    })?;
+
+    Ok(try_seconds(value * scale)?)
+  }
+}
+
+// Implement serialization as a string in the same format as `Display`, but
+// without rounding, in human-readable formats and as a number of seconds in
+// binary formats. Human-readable formats can also deserialize numbers of
+// seconds.
+impl serde::Serialize for Duration {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    struct Exact(Duration);
+
+    impl fmt::Display for Exact {
+      fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt_in_unit(f, None)
+      }
+    }
+
+    if serializer.is_human_readable() {
+      serializer.collect_str(&Exact(*self))
+    } else {
+      serializer.serialize_f64(self.seconds)
+    }
+  }
+}
+
+impl<'de> serde::Deserialize<'de> for Duration {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    if deserializer.is_human_readable() {
+      deserializer.deserialize_any(DeserializeVisitor)
+    } else {
+      deserializer.deserialize_f64(DeserializeVisitor)
+    }
+  }
+}
+
+struct DeserializeVisitor;
+
+impl<'de> serde::de::Visitor<'de> for DeserializeVisitor {
+  type Value = Duration;
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("a duration such as \"16.6ms\", \"60hz\", or a number of seconds")
+  }
+
+  fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+    value.parse().map_err(E::custom)
+  }
+
+  fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Self::Value, E> {
+    try_seconds(value).map_err(E::custom)
+  }
+
+  fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+    self.visit_f64(value as f64)
+  }
+
+  fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+    self.visit_f64(value as f64)
+  }
+}
+
+/// Unit suffixes accepted when parsing durations and their lengths in seconds.
+///
+/// Suffixes that end with another suffix come first so that they match first.
+const UNITS: [(&str, f64); 8] = [
+  ("ns", 0.000_000_001),
+  ("μs", 0.000_001),
+  ("us", 0.000_001),
+  ("ms", 0.001),
+  ("s", 1.0),
+  ("m", 60.0),
+  ("h", 3_600.0),
+  ("d", 86_400.0),
+];
+
+/// An error that occurred while computing a duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationError {
+  /// The duration would be negative.
+  Negative,
+  /// The duration would be infinite or not a number.
+  NotFinite,
+}
+
+impl std::error::Error for DurationError {}
+
+impl fmt::Display for DurationError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      DurationError::Negative => write!(f, "durations cannot be negative"),
+      DurationError::NotFinite => write!(f, "durations must be finite"),
+    }
+  }
+}
+
+/// An error that occurred while parsing a duration.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseDurationError {
+  /// The number in the string is invalid.
+  InvalidNumber(ParseFloatError),
+  /// The unit suffix in the string is not recognized.
+  UnknownUnit(String),
+  /// The string does not represent a valid duration.
+  InvalidDuration(DurationError),
+}
+
+impl std::error::Error for ParseDurationError {}
+
+impl fmt::Display for ParseDurationError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ParseDurationError::InvalidNumber(err) => write!(f, "invalid number: {}", err),
+      ParseDurationError::UnknownUnit(s) => write!(f, "unknown unit in {:?}", s),
+      ParseDurationError::InvalidDuration(err) => write!(f, "{}", err),
+    }
+  }
+}
+
+// Implement `From` to convert from a float parsing error.
+impl From<ParseFloatError> for ParseDurationError {
+  fn from(err: ParseFloatError) -> Self {
+    ParseDurationError::InvalidNumber(err)
+  }
+}
+
+// Implement `From` to convert from a duration error.
+impl From<DurationError> for ParseDurationError {
+  fn from(err: DurationError) -> Self {
+    ParseDurationError::InvalidDuration(err)
+  }
+}
+
 /// Returns a value representing a duration of `1.0 / hz` seconds.
 ///
 /// # Panics
 ///
-/// This function will panic if `hz` is a negative number.
+/// This function will panic if `hz` is not a positive number.
 pub fn hz(hz: f64) -> Duration {
-  seconds(1.0 / hz)
+  try_hz(hz).expect("invalid frequency")
+}
+
+/// Returns a value representing a duration of `1.0 / hz` seconds, or an error
+/// if `hz` is not a positive number.
+pub fn try_hz(hz: f64) -> Result<Duration, DurationError> {
+  try_seconds(1.0 / hz)
 }
 
 /// Returns a value representing a duration of the given number of seconds.
 ///
 /// # Panics
 ///
-/// This function will panic if `seconds` is a negative number.
+/// This function will panic if `seconds` is negative or not finite.
 pub fn seconds(seconds: f64) -> Duration {
+  try_seconds(seconds).expect("invalid duration")
+}
+
+/// Returns a value representing a duration of the given number of seconds, or
+/// an error if `seconds` is negative or not finite.
+pub fn try_seconds(seconds: f64) -> Result<Duration, DurationError> {
+  if !seconds.is_finite() {
+    return Err(DurationError::NotFinite);
+  }
+
   if seconds < 0.0 {
-    panic!("durations cannot be negative");
+    return Err(DurationError::Negative);
   }
 
-  Duration { seconds }
+  // Normalize negative zero.
+  Ok(Duration { seconds: seconds.max(0.0) })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+  struct Settings {
+    delay: Duration,
+  }
+
+  #[test]
+  fn durations_parse_from_units_and_frequencies() {
+    assert_eq!("2s".parse(), Ok(seconds(2.0)));
+    assert_eq!(" 1.5 m ".parse(), Ok(seconds(90.0)));
+    assert_eq!("250ms".parse(), Ok(seconds(250.0 * 0.001)));
+    assert_eq!("3μs".parse(), Ok(seconds(3.0 * 0.000_001)));
+    assert_eq!("3us".parse(), Ok(seconds(3.0 * 0.000_001)));
+    assert_eq!("60hz".parse(), Ok(hz(60.0)));
+    assert_eq!("0".parse(), Ok(Duration::ZERO));
+    assert_eq!("12".parse(), Ok(seconds(12.0)));
+  }
+
+  #[test]
+  fn invalid_durations_do_not_parse() {
+    assert_eq!(
+      "5 parsecs".parse::<Duration>(),
+      Err(ParseDurationError::UnknownUnit("5 parsecs".into()))
+    );
+    assert!(matches!("fast".parse::<Duration>(), Err(ParseDurationError::UnknownUnit(_))));
+    assert!(matches!("1.2.3s".parse::<Duration>(), Err(ParseDurationError::InvalidNumber(_))));
+    assert_eq!("-1s".parse::<Duration>(), Err(DurationError::Negative.into()));
+    assert_eq!("0hz".parse::<Duration>(), Err(DurationError::NotFinite.into()));
+  }
+
+  #[test]
+  fn durations_round_trip_through_serde() {
+    for duration in
+      [Duration::ZERO, seconds(1.0 / 3.0), hz(60.0), seconds(0.0000042), seconds(90_000.5)]
+    {
+      let settings = Settings { delay: duration };
+      let text = toml::to_string(&settings).unwrap();
+
+      assert_eq!(toml::from_str::<Settings>(&text).unwrap(), settings, "{}", text);
+    }
+
+    assert_eq!(toml::from_str::<Settings>("delay = 2").unwrap().delay, seconds(2.0));
+    assert_eq!(toml::from_str::<Settings>("delay = 0.5").unwrap().delay, seconds(0.5));
+    assert_eq!(toml::from_str::<Settings>("delay = \"60hz\"").unwrap().delay, hz(60.0));
+    assert!(toml::from_str::<Settings>("delay = -1").is_err());
+  }
+
+  #[test]
+  fn durations_round_trip_through_binary_formats() {
+    for duration in [Duration::ZERO, seconds(1.0 / 3.0), hz(60.0), seconds(90_000.5)] {
+      let settings = Settings { delay: duration };
+      let bytes = bincode::serialize(&settings).unwrap();
+
+      assert_eq!(bytes.len(), 8);
+      assert_eq!(bincode::deserialize::<Settings>(&bytes).unwrap(), settings);
+    }
+
+    assert!(bincode::deserialize::<Settings>(&bincode::serialize(&-1.0f64).unwrap()).is_err());
+  }
 }
@@ -0,0 +1,299 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use std::collections::VecDeque;
+
+/// Records frame times in a rolling window and computes statistics on them.
+#[derive(Debug)]
+pub struct FrameStats {
+  frame_times: VecDeque<Duration>,
+  window_size: usize,
+  logger: Option<log::Logger>,
+  summary_interval: Duration,
+  since_summary: Duration,
+}
+
+impl FrameStats {
+  /// The default number of frames in the rolling window.
+  pub const DEFAULT_WINDOW_SIZE: usize = 240;
+
+  /// Creates a new, empty set of frame statistics.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Returns the maximum number of frames in the rolling window.
+  pub fn window_size(&self) -> usize {
+    self.window_size
+  }
+
+  /// Sets the maximum number of frames in the rolling window, discarding the
+  /// oldest frames if there are more.
+  ///
+  /// # Panics
+  ///
+  /// This function panics if `value` is zero.
+  pub fn set_window_size(&mut self, value: usize) {
+    assert!(value > 0, "window size must be at least one frame");
+
+    self.window_size = value;
+
+    while self.frame_times.len() > value {
+      self.frame_times.pop_front();
+    }
+  }
+
+  /// Sets the logger used to log summaries.
+  pub fn set_logger(&mut self, logger: log::Logger) {
+    self.logger = Some(logger);
+  }
+
+  /// Sets how often to log a summary of the statistics at the `debug` level.
+  ///
+  /// Summaries are only logged if a logger is set. If the interval is zero,
+  /// which is the default, no summaries are logged.
+  pub fn set_summary_interval(&mut self, value: Duration) {
+    self.summary_interval = value;
+  }
+
+  /// Records the time of a frame, such as the unscaled elapsed delta of a
+  /// `Clock` after it is ticked.
+  pub fn record(&mut self, frame_time: Duration) {
+    if self.frame_times.len() == self.window_size {
+      self.frame_times.pop_front();
+    }
+
+    self.frame_times.push_back(frame_time);
+
+    if self.summary_interval == Duration::ZERO {
+      return;
+    }
+
+    self.since_summary += frame_time;
+
+    if self.since_summary >= self.summary_interval {
+      self.since_summary = Duration::ZERO;
+
+      if let Some(logger) = &self.logger {
+        log::debug!(logger, "frame stats";
+          "fps" => format!("{:.1}", self.fps()),
+          "average" => self.average(),
+          "min" => self.min(),
+          "max" => self.max(),
+          "1% low" => self.low(0.01),
+        );
+      }
+    }
+  }
+
+  /// Removes all recorded frames.
+  pub fn clear(&mut self) {
+    self.frame_times.clear();
+    self.since_summary = Duration::ZERO;
+  }
+
+  /// Returns the number of frames in the rolling window.
+  pub fn len(&self) -> usize {
+    self.frame_times.len()
+  }
+
+  /// Returns `true` if no frames have been recorded.
+  pub fn is_empty(&self) -> bool {
+    self.frame_times.is_empty()
+  }
+
+  /// Returns an iterator over the frame times in the rolling window from
+  /// oldest to newest.
+  pub fn frame_times(&self) -> impl Iterator<Item = Duration> + '_ {
+    self.frame_times.iter().cloned()
+  }
+
+  /// Returns the average frame time, or zero if no frames have been recorded.
+  pub fn average(&self) -> Duration {
+    if self.frame_times.is_empty() {
+      return Duration::ZERO;
+    }
+
+    let total: f64 = self.frame_times.iter().map(|t| t.as_secs()).sum();
+
+    seconds(total / self.frame_times.len() as f64)
+  }
+
+  /// Returns the average number of frames per second, or zero if no frames
+  /// have been recorded.
+  pub fn fps(&self) -> f64 {
+    let average = self.average().as_secs();
+
+    if average > 0.0 {
+      1.0 / average
+    } else {
+      0.0
+    }
+  }
+
+  /// Returns the shortest frame time, or zero if no frames have been recorded.
+  pub fn min(&self) -> Duration {
+    self
+      .frame_times
+      .iter()
+      .cloned()
+      .fold(None, |min, t| match min {
+        Some(min) if min <= t => Some(min),
+        _ => Some(t),
+      })
+      .unwrap_or_default()
+  }
+
+  /// Returns the longest frame time, or zero if no frames have been recorded.
+  pub fn max(&self) -> Duration {
+    self.frame_times.iter().cloned().fold(Duration::ZERO, |max, t| if t > max { t } else { max })
+  }
+
+  /// Returns the frame time below which the given fraction of frames fall,
+  /// such as `0.99` for the 99th percentile, or zero if no frames have been
+  /// recorded.
+  pub fn percentile(&self, fraction: f64) -> Duration {
+    let sorted = self.sorted();
+
+    if sorted.is_empty() {
+      return Duration::ZERO;
+    }
+
+    let index = (fraction.clamp(0.0, 1.0) * (sorted.len() - 1) as f64).round() as usize;
+
+    sorted[index]
+  }
+
+  /// Returns the average time of the given fraction of longest frames, such as
+  /// `0.01` for the "1% low", or zero if no frames have been recorded.
+  ///
+  /// At least one frame is always included.
+  pub fn low(&self, fraction: f64) -> Duration {
+    let sorted = self.sorted();
+
+    if sorted.is_empty() {
+      return Duration::ZERO;
+    }
+
+    let count = ((sorted.len() as f64 * fraction.clamp(0.0, 1.0)).ceil() as usize).max(1);
+    let total: f64 = sorted.iter().rev().take(count).map(|t| t.as_secs()).sum();
+
+    seconds(total / count as f64)
+  }
+
+  /// Returns a histogram of frame times with `bucket_count` buckets each
+  /// covering `bucket_width`, starting from zero.
+  ///
+  /// Each element is the number of frames in the bucket. Frames longer than
+  /// the last bucket are counted in the last bucket.
+  pub fn histogram(&self, bucket_width: Duration, bucket_count: usize) -> Vec<usize> {
+    let mut buckets = vec![0; bucket_count];
+
+    if bucket_count == 0 {
+      return buckets;
+    }
+
+    for time in &self.frame_times {
+      let index = if bucket_width > Duration::ZERO {
+        (time.as_secs() / bucket_width.as_secs()) as usize
+      } else {
+        bucket_count - 1
+      };
+
+      buckets[index.min(bucket_count - 1)] += 1;
+    }
+
+    buckets
+  }
+
+  /// Returns the frame times in the rolling window from shortest to longest.
+  fn sorted(&self) -> Vec<Duration> {
+    let mut sorted: Vec<_> = self.frame_times.iter().cloned().collect();
+
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal));
+    sorted
+  }
+}
+
+impl Default for FrameStats {
+  fn default() -> Self {
+    Self {
+      frame_times: VecDeque::with_capacity(Self::DEFAULT_WINDOW_SIZE),
+      window_size: Self::DEFAULT_WINDOW_SIZE,
+      logger: None,
+      summary_interval: Duration::ZERO,
+      since_summary: Duration::ZERO,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Returns stats with frame times of 1 to 100 seconds, recorded out of
+  /// order.
+  fn stats() -> FrameStats {
+    let mut stats = FrameStats::new();
+
+    for i in 0..100 {
+      stats.record(seconds(((i * 37) % 100 + 1) as f64));
+    }
+
+    stats
+  }
+
+  #[test]
+  fn percentiles_come_from_sorted_frame_times() {
+    let stats = stats();
+
+    assert_eq!(stats.percentile(0.0), seconds(1.0));
+    assert_eq!(stats.percentile(0.5), seconds(51.0));
+    assert_eq!(stats.percentile(0.99), seconds(99.0));
+    assert_eq!(stats.percentile(1.0), seconds(100.0));
+    assert_eq!(stats.percentile(2.0), seconds(100.0));
+    assert_eq!(stats.min(), seconds(1.0));
+    assert_eq!(stats.max(), seconds(100.0));
+    assert_eq!(stats.average(), seconds(50.5));
+  }
+
+  #[test]
+  fn lows_average_the_longest_frames() {
+    let stats = stats();
+
+    assert_eq!(stats.low(0.01), seconds(100.0));
+    assert_eq!(stats.low(0.05), seconds(98.0));
+    assert_eq!(stats.low(0.0), seconds(100.0));
+  }
+
+  #[test]
+  fn empty_stats_are_zero() {
+    let mut stats = stats();
+
+    stats.clear();
+
+    assert_eq!(stats.percentile(0.5), Duration::ZERO);
+    assert_eq!(stats.low(0.01), Duration::ZERO);
+    assert_eq!(stats.fps(), 0.0);
+  }
+
+  #[test]
+  fn window_keeps_the_newest_frames() {
+    let mut stats = FrameStats::new();
+
+    stats.set_window_size(3);
+
+    for i in 1..=5 {
+      stats.record(seconds(i as f64));
+    }
+
+    assert_eq!(stats.frame_times().collect::<Vec<_>>(), [seconds(3.0), seconds(4.0), seconds(5.0)]);
+    assert_eq!(stats.percentile(0.5), seconds(4.0));
+
+    stats.set_window_size(1);
+
+    assert_eq!(stats.frame_times().collect::<Vec<_>>(), [seconds(5.0)]);
+  }
+}
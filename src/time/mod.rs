@@ -4,9 +4,15 @@
 
 mod clock;
 mod duration;
+mod frame_stats;
 mod instant;
+mod source;
+mod timers;
+mod tweens;
 
-pub use self::{clock::*, duration::*, instant::*};
+pub use self::{
+  clock::*, duration::*, frame_stats::*, instant::*, source::*, timers::*, tweens::*,
+};
 
 use super::*;
 
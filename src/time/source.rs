@@ -0,0 +1,70 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+
+/// A source of the current time for a `Clock`.
+pub trait TimeSource: fmt::Debug + Send + Sync {
+  /// Returns the current instant in time.
+  fn now(&self) -> Instant;
+
+  /// Blocks until the given duration of time has passed.
+  fn sleep(&self, duration: Duration);
+}
+
+/// A `TimeSource` that reads the system's monotonic clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealTime;
+
+impl TimeSource for RealTime {
+  fn now(&self) -> Instant {
+    now()
+  }
+
+  fn sleep(&self, duration: Duration) {
+    spin_sleep(duration);
+  }
+}
+
+/// A `TimeSource` whose time only passes when it is advanced manually.
+///
+/// Sleeping on a virtual time source returns immediately without advancing
+/// time, so code driven by a `Clock` with a virtual source runs instantly and
+/// repeats exactly. Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct VirtualTime {
+  start: std::time::Instant,
+  elapsed: Arc<Mutex<std::time::Duration>>,
+}
+
+impl VirtualTime {
+  /// Creates a new virtual time source starting at the current instant.
+  pub fn new() -> Self {
+    Self { start: std::time::Instant::now(), elapsed: Default::default() }
+  }
+
+  /// Returns the total duration the time source has been advanced by.
+  pub fn elapsed(&self) -> Duration {
+    (*self.elapsed.lock()).into()
+  }
+
+  /// Advances the time of the source by the given duration.
+  pub fn advance(&self, duration: Duration) {
+    *self.elapsed.lock() += duration.try_into().expect("could not advance by given duration");
+  }
+}
+
+impl Default for VirtualTime {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl TimeSource for VirtualTime {
+  fn now(&self) -> Instant {
+    (self.start + *self.elapsed.lock()).into()
+  }
+
+  fn sleep(&self, _: Duration) {}
+}
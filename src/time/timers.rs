@@ -0,0 +1,434 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use crate::ecs::events::EventChannel;
+use crate::ecs::storage::DenseVecStorage;
+use crate::ecs::{self, Entity, Join as _, System};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{self, Poll, Waker};
+
+lazy_static! {
+  static ref DEFAULT_TIMERS: RwLock<Option<Timers>> = RwLock::new(None);
+}
+
+/// A timer that expires after a duration of game time, either once or
+/// repeatedly.
+///
+/// A timer can be started in the `Timers` resource or added to an entity as a
+/// component. Either way, it is advanced by the `UpdateTimers` system, which
+/// writes a `TimerExpired` event each time it expires.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timer {
+  duration: Duration,
+  elapsed: Duration,
+  repeating: bool,
+  paused: bool,
+  finished: bool,
+}
+
+impl Timer {
+  /// Creates a new timer that expires once after the given duration.
+  pub fn once(duration: Duration) -> Self {
+    Self { duration, elapsed: Duration::ZERO, repeating: false, paused: false, finished: false }
+  }
+
+  /// Creates a new timer that expires every time the given duration elapses.
+  pub fn repeating(duration: Duration) -> Self {
+    Self { repeating: true, ..Self::once(duration) }
+  }
+
+  /// Returns the duration after which the timer expires.
+  pub fn duration(&self) -> Duration {
+    self.duration
+  }
+
+  /// Returns the time elapsed since the timer was started or last expired.
+  pub fn elapsed(&self) -> Duration {
+    self.elapsed
+  }
+
+  /// Returns the time remaining until the timer next expires.
+  pub fn remaining(&self) -> Duration {
+    self.duration - self.elapsed
+  }
+
+  /// Returns `true` if the timer expires repeatedly.
+  pub fn is_repeating(&self) -> bool {
+    self.repeating
+  }
+
+  /// Returns `true` if the timer is paused.
+  pub fn is_paused(&self) -> bool {
+    self.paused
+  }
+
+  /// Sets whether the timer is paused. Paused timers do not advance.
+  pub fn set_paused(&mut self, value: bool) {
+    self.paused = value;
+  }
+
+  /// Returns `true` if the timer expires once and has expired.
+  pub fn is_finished(&self) -> bool {
+    self.finished
+  }
+
+  /// Restarts the timer from zero.
+  pub fn reset(&mut self) {
+    self.elapsed = Duration::ZERO;
+    self.finished = false;
+  }
+
+  /// Advances the timer by the given duration and returns the number of times
+  /// it expired.
+  ///
+  /// A repeating timer with a zero duration expires once per call.
+  pub fn advance(&mut self, delta: Duration) -> u64 {
+    if self.paused || self.finished {
+      return 0;
+    }
+
+    self.elapsed += delta;
+
+    if self.elapsed < self.duration {
+      return 0;
+    }
+
+    if !self.repeating || self.duration == Duration::ZERO {
+      self.elapsed = if self.repeating { Duration::ZERO } else { self.duration };
+      self.finished = !self.repeating;
+
+      return 1;
+    }
+
+    let count = (self.elapsed.as_secs() / self.duration.as_secs()).floor();
+
+    self.elapsed = self.elapsed - self.duration * count;
+
+    count as u64
+  }
+}
+
+impl ecs::Component for Timer {
+  type Storage = DenseVecStorage<Self>;
+}
+
+/// A unique identifier for a timer started in the `Timers` resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId(u64);
+
+/// An event written when a timer expires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerExpired {
+  /// A timer in the `Timers` resource expired.
+  Timer(TimerId),
+  /// The `Timer` component of an entity expired.
+  Entity(Entity),
+}
+
+/// A resource containing timers and delays that are advanced by game time.
+///
+/// Clones of a `Timers` resource share the same timers, so a clone can be
+/// kept to start timers and create `Delay` futures outside of systems.
+#[derive(Debug, Clone, Default)]
+pub struct Timers {
+  state: Arc<Mutex<TimersState>>,
+}
+
+#[derive(Debug, Default)]
+struct TimersState {
+  elapsed: Duration,
+  next_id: u64,
+  timers: HashMap<TimerId, Timer>,
+  delays: HashMap<u64, (Duration, Option<Waker>)>,
+}
+
+impl TimersState {
+  fn next_id(&mut self) -> u64 {
+    self.next_id += 1;
+    self.next_id
+  }
+}
+
+impl Timers {
+  /// Creates a new, empty set of timers.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Returns the total game time the timers have been advanced by.
+  pub fn elapsed(&self) -> Duration {
+    self.state.lock().elapsed
+  }
+
+  /// Starts a timer and returns its unique identifier.
+  pub fn start(&self, timer: Timer) -> TimerId {
+    let mut state = self.state.lock();
+    let id = TimerId(state.next_id());
+
+    state.timers.insert(id, timer);
+
+    id
+  }
+
+  /// Returns a copy of the timer with the given identifier if it has not
+  /// finished or been cancelled.
+  pub fn get(&self, id: TimerId) -> Option<Timer> {
+    self.state.lock().timers.get(&id).cloned()
+  }
+
+  /// Sets whether the timer with the given identifier is paused.
+  ///
+  /// Returns `false` if there is no such timer.
+  pub fn set_paused(&self, id: TimerId, value: bool) -> bool {
+    match self.state.lock().timers.get_mut(&id) {
+      Some(timer) => {
+        timer.set_paused(value);
+        true
+      }
+
+      None => false,
+    }
+  }
+
+  /// Cancels the timer with the given identifier.
+  ///
+  /// Returns `false` if there is no such timer.
+  pub fn cancel(&self, id: TimerId) -> bool {
+    self.state.lock().timers.remove(&id).is_some()
+  }
+
+  /// Returns a future that resolves once the timers have been advanced by the
+  /// given duration of game time.
+  pub fn delay(&self, duration: Duration) -> Delay {
+    let mut state = self.state.lock();
+    let id = state.next_id();
+    let deadline = state.elapsed + duration;
+
+    state.delays.insert(id, (deadline, None));
+
+    Delay { timers: self.clone(), id }
+  }
+
+  /// Advances all timers and delays by the given duration, calling `expired`
+  /// once for each time a timer expires.
+  ///
+  /// Timers that expire once are removed after they expire. This is normally
+  /// called by the `UpdateTimers` system.
+  pub fn advance(&self, delta: Duration, mut expired: impl FnMut(TimerId)) {
+    let mut state = self.state.lock();
+
+    state.elapsed += delta;
+
+    for (id, timer) in &mut state.timers {
+      for _ in 0..timer.advance(delta) {
+        expired(*id);
+      }
+    }
+
+    state.timers.retain(|_, timer| !timer.is_finished());
+
+    // Wake any tasks waiting on delays that are now complete. Wake them after
+    // releasing the lock because a waker may poll the task immediately.
+    let elapsed = state.elapsed;
+
+    let wakers: Vec<_> = state
+      .delays
+      .values_mut()
+      .filter(|(deadline, _)| *deadline <= elapsed)
+      .filter_map(|(_, waker)| waker.take())
+      .collect();
+
+    drop(state);
+
+    for waker in wakers {
+      waker.wake();
+    }
+  }
+}
+
+/// A future that resolves after a duration of game time, created with
+/// `delay()` or `Timers::delay()`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Delay {
+  timers: Timers,
+  id: u64,
+}
+
+impl Future for Delay {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut task::Context) -> Poll<()> {
+    let mut state = self.timers.state.lock();
+    let elapsed = state.elapsed;
+
+    match state.delays.get_mut(&self.id) {
+      Some((deadline, _)) if *deadline <= elapsed => Poll::Ready(()),
+      Some((_, waker)) => {
+        *waker = Some(cx.waker().clone());
+        Poll::Pending
+      }
+
+      None => Poll::Ready(()),
+    }
+  }
+}
+
+impl Drop for Delay {
+  fn drop(&mut self) {
+    self.timers.state.lock().delays.remove(&self.id);
+  }
+}
+
+/// Returns a future that resolves once the default timers have been advanced
+/// by the given duration of game time.
+///
+/// The default timers are the `Timers` resource of the ECS instance most
+/// recently set up with `set_up_timers()`. Use `Timers::delay()` to wait on the
+/// timers of a particular instance.
+///
+/// Unlike `sleep()`, the delay follows the game clock, so it is stretched by
+/// the clock's time scale and does not progress while the clock is paused.
+///
+/// # Panics
+///
+/// Panics if `set_up_timers()` has not been called.
+pub fn delay(duration: Duration) -> Delay {
+  let timers = DEFAULT_TIMERS.read().clone().expect("time::set_up_timers has not been called");
+
+  timers.delay(duration)
+}
+
+/// A system that advances the `Timers` resource and all `Timer` components by
+/// the game time of the updates of the most recent `Clock` tick, writing a
+/// `TimerExpired` event for each expiry.
+#[derive(Debug, Default)]
+pub struct UpdateTimers;
+
+impl UpdateTimers {
+  /// Returns the name the system is scheduled with by `set_up_timers()`.
+  pub fn name() -> &'static str {
+    "time::UpdateTimers"
+  }
+}
+
+impl<'a> System<'a> for UpdateTimers {
+  type SystemData = (
+    specs::Entities<'a>,
+    specs::ReadExpect<'a, Clock>,
+    specs::ReadExpect<'a, Timers>,
+    specs::WriteStorage<'a, Timer>,
+    specs::WriteExpect<'a, EventChannel<TimerExpired>>,
+  );
+
+  fn run(&mut self, (entities, clock, timers, mut components, mut events): Self::SystemData) {
+    let delta = clock.updates_delta();
+
+    timers.advance(delta, |id| events.single_write(TimerExpired::Timer(id)));
+
+    for (entity, timer) in (&entities, &mut components).join() {
+      for _ in 0..timer.advance(delta) {
+        events.single_write(TimerExpired::Entity(entity));
+      }
+    }
+  }
+}
+
+/// Sets up timers in an ECS instance that has a `Clock` resource.
+///
+/// This function adds the `Timers` resource, registers the `Timer` component,
+/// adds an event channel for `TimerExpired` events, and schedules the
+/// `UpdateTimers` system to run before updates. The new `Timers` resource
+/// becomes the default used by `delay()`.
+pub fn set_up_timers(ctx: &mut ecs::Context) {
+  let timers = Timers::new();
+
+  *DEFAULT_TIMERS.write() = Some(timers.clone());
+
+  ctx.put_resource(timers);
+  ctx.register_component::<Timer>();
+  ctx.add_event_channel::<TimerExpired>();
+
+  ctx.schedule_after(ecs::Phase::BeforeUpdate, UpdateTimers::name(), &[], UpdateTimers);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn repeating_timer_counts_each_expiry() {
+    let mut timer = Timer::repeating(seconds(1.0));
+
+    assert_eq!(timer.advance(seconds(0.5)), 0);
+    assert_eq!(timer.advance(seconds(2.75)), 3);
+    assert_eq!(timer.elapsed(), seconds(0.25));
+
+    let mut timer = Timer::once(seconds(1.0));
+
+    assert_eq!(timer.advance(seconds(3.0)), 1);
+    assert!(timer.is_finished());
+    assert_eq!(timer.advance(seconds(3.0)), 0);
+  }
+
+  /// A waker that uses the timers when woken, like an executor that polls
+  /// tasks immediately.
+  struct ReentrantWaker(Timers);
+
+  impl task::Wake for ReentrantWaker {
+    fn wake(self: Arc<Self>) {
+      self.0.elapsed();
+    }
+  }
+
+  #[test]
+  fn delays_are_woken_without_holding_the_lock() {
+    let timers = Timers::new();
+    let mut delay = timers.delay(seconds(1.0));
+    let waker = Waker::from(Arc::new(ReentrantWaker(timers.clone())));
+    let mut cx = task::Context::from_waker(&waker);
+
+    assert_eq!(Pin::new(&mut delay).poll(&mut cx), Poll::Pending);
+
+    timers.advance(seconds(1.0), |_| {});
+
+    assert_eq!(Pin::new(&mut delay).poll(&mut cx), Poll::Ready(()));
+  }
+
+  #[test]
+  fn delays_follow_the_game_clock_of_the_default_timers() {
+    let time = VirtualTime::new();
+    let mut clock = Clock::new();
+
+    clock.set_time_source(time.clone());
+    clock.tick();
+
+    let mut ctx = ecs::Context::new();
+
+    ctx.put_resource(clock);
+
+    set_up_timers(&mut ctx);
+
+    let mut delay = delay(seconds(1.0));
+    let waker = futures::task::noop_waker();
+    let mut cx = task::Context::from_waker(&waker);
+
+    ctx.resource_mut::<Clock>().set_paused(true);
+    time.advance(seconds(2.0));
+    ctx.resource_mut::<Clock>().tick();
+    ctx.run_phase(ecs::Phase::BeforeUpdate);
+
+    assert_eq!(Pin::new(&mut delay).poll(&mut cx), Poll::Pending);
+
+    ctx.resource_mut::<Clock>().set_paused(false);
+    time.advance(seconds(1.0));
+    ctx.resource_mut::<Clock>().tick();
+    ctx.run_phase(ecs::Phase::BeforeUpdate);
+
+    assert_eq!(Pin::new(&mut delay).poll(&mut cx), Poll::Ready(()));
+  }
+}
@@ -0,0 +1,382 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::*;
+use crate::ecs::events::EventChannel;
+use crate::ecs::storage::DenseVecStorage;
+use crate::ecs::{self, Entity, Join as _, System};
+use crate::math::ease::Ease;
+use crate::math::Lerp;
+use std::marker::PhantomData;
+
+/// An animation that interpolates a value through a sequence of segments over
+/// game time.
+///
+/// A tween starts at a value and moves to the target of each segment in turn,
+/// shaping its progress with each segment's easing curve. It can repeat a
+/// number of times or forever, and with yoyo enabled every other repeat plays
+/// backwards.
+///
+/// As a component, a tween is advanced by an `UpdateTweens` system, which
+/// writes a `TweenFinished<T>` event when it finishes. Finished tweens stay on
+/// their entity holding their final value.
+#[derive(Debug, Clone)]
+pub struct Tween<T> {
+  start: T,
+  segments: Vec<Segment<T>>,
+  duration: Duration,
+  value: T,
+  elapsed: Duration,
+  pass: u32,
+  repeat: Repeat,
+  yoyo: bool,
+  paused: bool,
+  finished: bool,
+}
+
+/// One segment of a `Tween`.
+#[derive(Debug, Clone)]
+struct Segment<T> {
+  target: T,
+  duration: Duration,
+  ease: Ease,
+}
+
+/// How many times a `Tween` repeats after playing once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Repeat {
+  /// The tween plays once.
+  #[default]
+  Never,
+  /// The tween plays once then repeats the given number of times.
+  Times(u32),
+  /// The tween repeats until it is removed.
+  Forever,
+}
+
+impl<T: Lerp + Clone> Tween<T> {
+  /// Creates a new tween that moves from one value to another over the given
+  /// duration.
+  pub fn new(from: T, to: T, duration: Duration, ease: Ease) -> Self {
+    let mut tween = Tween {
+      start: from.clone(),
+      segments: Vec::new(),
+      duration: Duration::ZERO,
+      value: from,
+      elapsed: Duration::ZERO,
+      pass: 0,
+      repeat: Repeat::Never,
+      yoyo: false,
+      paused: false,
+      finished: false,
+    };
+
+    tween.push(to, duration, ease);
+    tween
+  }
+
+  /// Returns the tween with another segment that moves from the target of the
+  /// last segment to a new target over the given duration.
+  pub fn then(mut self, to: T, duration: Duration, ease: Ease) -> Self {
+    self.push(to, duration, ease);
+    self
+  }
+
+  /// Adds a segment to the end of the sequence.
+  pub fn push(&mut self, to: T, duration: Duration, ease: Ease) {
+    self.segments.push(Segment { target: to, duration, ease });
+    self.duration += duration;
+  }
+
+  /// Returns the current value of the tween.
+  pub fn value(&self) -> &T {
+    &self.value
+  }
+
+  /// Returns the duration of one play through all segments.
+  pub fn duration(&self) -> Duration {
+    self.duration
+  }
+
+  /// Returns the time elapsed in the current play through the segments.
+  pub fn elapsed(&self) -> Duration {
+    self.elapsed
+  }
+
+  /// Returns how many times the tween has repeated so far.
+  pub fn repeated(&self) -> u32 {
+    self.pass
+  }
+
+  /// Sets how many times the tween repeats after playing once.
+  pub fn set_repeat(&mut self, repeat: Repeat) {
+    self.repeat = repeat;
+  }
+
+  /// Sets whether every other repeat of the tween plays backwards.
+  pub fn set_yoyo(&mut self, value: bool) {
+    self.yoyo = value;
+  }
+
+  /// Returns `true` if the tween is paused.
+  pub fn is_paused(&self) -> bool {
+    self.paused
+  }
+
+  /// Sets whether the tween is paused. Paused tweens do not advance.
+  pub fn set_paused(&mut self, value: bool) {
+    self.paused = value;
+  }
+
+  /// Returns `true` if the tween has played all of its repeats.
+  pub fn is_finished(&self) -> bool {
+    self.finished
+  }
+
+  /// Restarts the tween from its starting value.
+  pub fn reset(&mut self) {
+    self.elapsed = Duration::ZERO;
+    self.pass = 0;
+    self.finished = false;
+    self.value = self.start.clone();
+  }
+
+  /// Advances the tween by the given duration, updating its value, and
+  /// returns `true` if it finished during this call.
+  pub fn advance(&mut self, delta: Duration) -> bool {
+    if self.paused || self.finished {
+      return false;
+    }
+
+    self.elapsed += delta;
+
+    while self.elapsed >= self.duration {
+      let last_pass = match self.repeat {
+        Repeat::Never => true,
+        Repeat::Times(count) => self.pass >= count,
+        Repeat::Forever => false,
+      };
+
+      // A zero-length tween that repeats forever would loop endlessly, so it
+      // stays at the start of its current pass instead.
+      if last_pass || self.duration == Duration::ZERO {
+        self.elapsed = self.duration;
+        self.finished = last_pass;
+        break;
+      }
+
+      self.elapsed = self.elapsed - self.duration;
+      self.pass += 1;
+    }
+
+    self.value = self.sample();
+    self.finished
+  }
+
+  /// Returns the value at the current elapsed time and pass.
+  fn sample(&self) -> T {
+    let mut time = self.elapsed;
+
+    if self.yoyo && self.pass % 2 == 1 {
+      time = self.duration - time;
+    }
+
+    let mut from = &self.start;
+
+    for segment in &self.segments {
+      if time < segment.duration {
+        let t = (time.as_secs() / segment.duration.as_secs()) as f32;
+
+        return from.lerp(&segment.target, segment.ease.apply(t));
+      }
+
+      time = time - segment.duration;
+      from = &segment.target;
+    }
+
+    from.clone()
+  }
+}
+
+impl<T: Send + Sync + 'static> ecs::Component for Tween<T> {
+  type Storage = DenseVecStorage<Self>;
+}
+
+/// An event written when the `Tween<T>` component of an entity finishes.
+///
+/// Each type of tweened value has its own event channel, so a consumer can
+/// tell which of an entity's tweens finished.
+pub struct TweenFinished<T> {
+  /// The entity whose tween finished.
+  pub entity: Entity,
+  _value: PhantomData<fn() -> T>,
+}
+
+impl<T> TweenFinished<T> {
+  /// Creates a new event for the given entity.
+  pub fn new(entity: Entity) -> Self {
+    Self { entity, _value: PhantomData }
+  }
+}
+
+// Implement common traits without requiring them for `T`.
+
+impl<T> Clone for TweenFinished<T> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<T> Copy for TweenFinished<T> {}
+
+impl<T> PartialEq for TweenFinished<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.entity == other.entity
+  }
+}
+
+impl<T> Eq for TweenFinished<T> {}
+
+impl<T> fmt::Debug for TweenFinished<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_tuple("TweenFinished").field(&self.entity).finish()
+  }
+}
+
+/// A system that advances all `Tween<T>` components by the game time of the
+/// updates of the most recent `Clock` tick, writing a `TweenFinished<T>` event
+/// for each tween that finishes.
+#[derive(Debug)]
+pub struct UpdateTweens<T>(PhantomData<T>);
+
+impl<T> Default for UpdateTweens<T> {
+  fn default() -> Self {
+    UpdateTweens(PhantomData)
+  }
+}
+
+impl<'a, T: Lerp + Clone + Send + Sync + 'static> System<'a> for UpdateTweens<T> {
+  type SystemData = (
+    specs::Entities<'a>,
+    specs::ReadExpect<'a, Clock>,
+    specs::WriteStorage<'a, Tween<T>>,
+    specs::WriteExpect<'a, EventChannel<TweenFinished<T>>>,
+  );
+
+  fn run(&mut self, (entities, clock, mut tweens, mut events): Self::SystemData) {
+    let delta = clock.updates_delta();
+
+    for (entity, tween) in (&entities, &mut tweens).join() {
+      if tween.advance(delta) {
+        events.single_write(TweenFinished::new(entity));
+      }
+    }
+  }
+}
+
+/// Sets up tweens of values of type `T` in an ECS instance that has a `Clock`
+/// resource.
+///
+/// This function registers the `Tween<T>` component, adds an event channel
+/// for `TweenFinished<T>` events, and schedules an `UpdateTweens<T>` system to
+/// run before updates. Call it once for each type of value to tween.
+pub fn set_up_tweens<T: Lerp + Clone + Send + Sync + 'static>(ctx: &mut ecs::Context) {
+  ctx.register_component::<Tween<T>>();
+  ctx.add_event_channel::<TweenFinished<T>>();
+
+  ctx.schedule(ecs::Phase::BeforeUpdate, UpdateTweens::<T>::default());
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::gfx::Color;
+
+  #[test]
+  fn tween_repeats_with_yoyo_then_finishes() {
+    let mut tween =
+      Tween::new(0.0, 10.0, seconds(1.0), Ease::Linear).then(20.0, seconds(1.0), Ease::Linear);
+
+    tween.set_repeat(Repeat::Times(1));
+    tween.set_yoyo(true);
+
+    let mut values = Vec::new();
+
+    for _ in 0..8 {
+      let finished = tween.advance(seconds(0.5));
+
+      values.push((*tween.value(), finished));
+    }
+
+    assert_eq!(
+      values,
+      [
+        (5.0, false),
+        (10.0, false),
+        (15.0, false),
+        (20.0, false),
+        (15.0, false),
+        (10.0, false),
+        (5.0, false),
+        (0.0, true),
+      ]
+    );
+
+    assert!(tween.is_finished());
+    assert_eq!(tween.repeated(), 1);
+    assert!(!tween.advance(seconds(1.0)));
+
+    tween.reset();
+
+    assert_eq!(*tween.value(), 0.0);
+    assert!(!tween.is_finished());
+  }
+
+  #[test]
+  fn tween_without_repeats_finishes_at_its_target() {
+    let mut tween = Tween::new(0.0, 1.0, seconds(1.0), Ease::QuadIn);
+
+    assert!(!tween.advance(seconds(0.5)));
+    assert_eq!(*tween.value(), 0.25);
+    assert!(tween.advance(seconds(5.0)));
+    assert_eq!(*tween.value(), 1.0);
+  }
+
+  #[test]
+  fn finished_events_are_written_per_value_type() {
+    let time = VirtualTime::new();
+    let mut clock = Clock::new();
+
+    clock.set_time_source(time.clone());
+    clock.tick();
+
+    let mut ctx = ecs::Context::new();
+
+    ctx.put_resource(clock);
+
+    set_up_tweens::<f32>(&mut ctx);
+    set_up_tweens::<Color>(&mut ctx);
+
+    let entity = ctx.entities().create();
+
+    ctx.commit_entities();
+    ctx.components_mut().insert(entity, Tween::new(0.0f32, 1.0, seconds(1.0), Ease::Linear));
+    ctx
+      .components_mut()
+      .insert(entity, Tween::new(Color::BLACK, Color::WHITE, seconds(2.0), Ease::Linear));
+
+    let mut floats = ctx.register_event_reader::<TweenFinished<f32>>();
+    let mut colors = ctx.register_event_reader::<TweenFinished<Color>>();
+
+    time.advance(seconds(1.0));
+    ctx.resource_mut::<Clock>().tick();
+    ctx.run_phase(ecs::Phase::BeforeUpdate);
+
+    let finished: Vec<_> =
+      ctx.resource::<EventChannel<TweenFinished<f32>>>().read(&mut floats).cloned().collect();
+
+    assert_eq!(finished, [TweenFinished::new(entity)]);
+    assert_eq!(ctx.resource::<EventChannel<TweenFinished<Color>>>().read(&mut colors).count(), 0);
+  }
+}
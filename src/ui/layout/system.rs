@@ -99,7 +99,7 @@ fn calculate_size(
           if let Some(Layout::AspectRatioFill(r)) = child_layout {
             ratio = ratio.max(*r);
           } else if let Some(Layout::Constrained(c)) = child_layout {
-            ratio = ratio.max(c.largest_finite_size().ratio());
+            ratio = ratio.max(c.largest_finite_size().ratio() as f32);
           }
         }
       }
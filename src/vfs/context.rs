@@ -5,7 +5,7 @@
 use super::*;
 use std::env;
 use std::ffi::OsString;
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io::{self, Read as _};
 
 /// A virtual file system context.
@@ -120,6 +120,92 @@ impl Context {
       .unwrap_or_else(|| Err(io::ErrorKind::NotFound.into()))
   }
 
+  /// Opens a file in the virtual file system for appending, creating it if it
+  /// does not exist.
+  ///
+  /// Like `create()`, this function uses the first mount point that matches,
+  /// in reverse of the order in which they were added, and creates any parent
+  /// directories that do not exist.
+  pub fn append(&self, path: impl AsRef<Path>) -> io::Result<File> {
+    let path = path.as_ref();
+
+    self
+      .relative_to_each_mount(path, |fs_path| {
+        if let Some(parent) = FsPath::new(fs_path).parent() {
+          if let Err(err) = std::fs::create_dir_all(parent) {
+            return Some(Err(err));
+          }
+        }
+
+        match OpenOptions::new().append(true).create(true).open(&fs_path) {
+          Ok(file) => return Some(Ok(file)),
+
+          Err(err) => {
+            if err.kind() != io::ErrorKind::NotFound {
+              return Some(Err(err));
+            }
+          }
+        }
+
+        None
+      })
+      .unwrap_or_else(|| Err(io::ErrorKind::NotFound.into()))
+  }
+
+  /// Renames a file in the virtual file system, replacing any file that
+  /// already exists at the new path.
+  ///
+  /// This function searches for the file in mount points that match both paths
+  /// in reverse of the order they were added, and renames the first file that
+  /// exists on disk.
+  pub fn rename(&self, from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+
+    for mount in self.mounts.read().iter().rev() {
+      let (from, to) = match (from.strip_prefix(&mount.path), to.strip_prefix(&mount.path)) {
+        (Some(from), Some(to)) => (mount.fs_path_of(from), mount.fs_path_of(to)),
+        _ => continue,
+      };
+
+      match std::fs::rename(&from, &to) {
+        Ok(()) => return Ok(()),
+
+        Err(err) => {
+          if err.kind() != io::ErrorKind::NotFound {
+            return Err(err);
+          }
+        }
+      }
+    }
+
+    Err(io::ErrorKind::NotFound.into())
+  }
+
+  /// Removes a file from the virtual file system.
+  ///
+  /// This function searches for the file in matching mount points in reverse
+  /// of the order they were added, and removes the first file that exists on
+  /// disk.
+  pub fn remove_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+
+    self
+      .relative_to_each_mount(path, |fs_path| {
+        match std::fs::remove_file(fs_path) {
+          Ok(()) => return Some(Ok(())),
+
+          Err(err) => {
+            if err.kind() != io::ErrorKind::NotFound {
+              return Some(Err(err));
+            }
+          }
+        }
+
+        None
+      })
+      .unwrap_or_else(|| Err(io::ErrorKind::NotFound.into()))
+  }
+
   /// Calls `func` once for each mount point that matches `path`, providing an
   /// `OsString` containing the corresponding real file system path.
   ///
@@ -161,6 +247,17 @@ struct Mount {
   fs_path: FsPathBuf,
 }
 
+impl Mount {
+  /// Returns the real file system path of a path relative to the mount point.
+  fn fs_path_of(&self, relative: &Path) -> OsString {
+    let mut fs_path = OsString::from(&self.fs_path);
+
+    fs_path.push("/");
+    fs_path.push(relative);
+    fs_path
+  }
+}
+
 /// Creates a new `File` at the given path, creating all parent directories that
 /// do not already exist.
 fn create_all(path: impl AsRef<FsPath>) -> io::Result<File> {
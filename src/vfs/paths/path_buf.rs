@@ -76,6 +76,6 @@ impl ops::Deref for PathBuf {
 
 impl fmt::Display for PathBuf {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "{}", &*self)
+    write!(f, "{}", &**self)
   }
 }
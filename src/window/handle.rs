@@ -10,27 +10,47 @@ use super::*;
 pub struct Handle {
   window: Arc<winit::Window>,
   events: mpsc::UnboundedReceiver<winit::WindowEvent>,
+  proxy: winit::EventsLoopProxy,
+  closed: oneshot::Receiver<()>,
 }
 
 impl Handle {
   pub(super) fn new(
     window: winit::Window,
     events: mpsc::UnboundedReceiver<winit::WindowEvent>,
+    proxy: winit::EventsLoopProxy,
+    closed: oneshot::Receiver<()>,
   ) -> Self {
-    Self { window: Arc::new(window), events }
+    Self { window: Arc::new(window), events, proxy, closed }
+  }
+
+  /// Closes the window and waits for its event loop thread to stop.
+  pub fn close(self) {
+    let Handle { window, mut events, proxy, closed } = self;
+
+    drop(window);
+
+    events.close();
+
+    // Wake up the event loop so that it notices the closed channel.
+    let _ = proxy.wakeup();
+    let _ = block_on(closed);
   }
 
   /// Returns the next window event if one is available or `None` if there is no
   /// available event.
   pub fn next_event(&mut self) -> Option<Event> {
-    let event = self.events.try_next().ok()??;
+    // Skip any winit events that do not map to an `Event`.
+    loop {
+      let event = self.events.try_next().ok()??;
 
-    Some(match event {
-      winit::WindowEvent::CloseRequested => Event::CloseRequested,
-      winit::WindowEvent::Resized(_) => Event::Resized,
+      return Some(match event {
+        winit::WindowEvent::CloseRequested => Event::CloseRequested,
+        winit::WindowEvent::Resized(_) => Event::Resized,
 
-      _ => return None,
-    })
+        _ => continue,
+      });
+    }
   }
 
   /// Returns a reference to the underlying winit window.
@@ -10,7 +10,7 @@ pub use self::{handle::*, settings::*};
 use super::*;
 
 /// A window event.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Event {
   /// The user requested for the window to close, such as by clicking on the
   /// window's X button.
@@ -26,6 +26,7 @@ pub fn open(thread_scope: &thread::Scope, settings: Settings) -> Result<Handle,
   // Create channels to communicate with the window's event loop thread.
   let (send_events, recv_events) = mpsc::unbounded();
   let (send_window, recv_window) = oneshot::channel();
+  let (send_closed, recv_closed) = oneshot::channel();
 
   // Start the event loop thread.
   thread_scope.spawn(move |_| {
@@ -33,6 +34,7 @@ pub fn open(thread_scope: &thread::Scope, settings: Settings) -> Result<Handle,
     let mut events_loop = winit::EventsLoop::new();
 
     let monitor = events_loop.get_primary_monitor();
+    let proxy = events_loop.create_proxy();
 
     // Use the given size or a default size that is a multiple of 1280x720.
     let size = match settings.size {
@@ -63,28 +65,37 @@ pub fn open(thread_scope: &thread::Scope, settings: Settings) -> Result<Handle,
 
     let created = window.is_ok();
 
-    if send_window.send(window).is_err() || !created {
+    if send_window.send(window.map(|window| (window, proxy))).is_err() || !created {
       return;
     }
 
     // Run the event loop, sending events to the window handle, until the
     // channel is closed on the other end meaning all handles have been dropped
-    // and the window should close.
+    // and the window should close. `Handle::close()` wakes up the event loop
+    // after closing the channel so that it stops right away.
     events_loop.run_forever(|event| {
-      if let winit::Event::WindowEvent { event, .. } = event {
-        if send_events.unbounded_send(event).is_err() {
-          return winit::ControlFlow::Break;
-        }
+      let closed = match event {
+        winit::Event::WindowEvent { event, .. } => send_events.unbounded_send(event).is_err(),
+        winit::Event::Awakened => send_events.is_closed(),
+        _ => false,
+      };
+
+      if closed {
+        winit::ControlFlow::Break
+      } else {
+        winit::ControlFlow::Continue
       }
-
-      winit::ControlFlow::Continue
     });
+
+    drop(events_loop);
+
+    let _ = send_closed.send(());
   });
 
   // Receive the window from the background thread and wrap it.
-  let window = block_on(recv_window)??;
+  let (window, proxy) = block_on(recv_window)??;
 
-  Ok(Handle::new(window, recv_events))
+  Ok(Handle::new(window, recv_events, proxy, recv_closed))
 }
 
 #[derive(Debug)]
@@ -14,6 +14,7 @@ lazy_static! {
   };
 }
 
+/// Settings for the application window, stored in the `[window]` section.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Settings {
   #[serde(default)]
@@ -24,6 +25,10 @@ pub struct Settings {
   pub size: Option<Size<f64>>,
 }
 
+impl crate::settings::Section for Settings {
+  const NAME: &'static str = "window";
+}
+
 pub(super) fn default_title() -> String {
   DEFAULT_TITLE.clone()
 }